@@ -0,0 +1,109 @@
+//! Compares the grid broadphase against testing every brick, on a 100x100 board.
+//! Run with `cargo bench --bench broadphase`.
+
+use std::time::{Duration, Instant};
+use cgmath::{vec2, Vector2};
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg32;
+use breakout::broadphase::Grid;
+
+const COLUMNS: usize = 100;
+const ROWS: usize = 100;
+const BRICK_SIZE: Vector2<f32> = Vector2 { x: 8.0, y: 3.0 };
+const QUERIES: usize = 10_000;
+
+struct Board {
+  bricks: Vec<(Vector2<f32>, Vector2<f32>)>,
+  destroyed: Vec<bool>,
+}
+
+fn board() -> Board {
+  let mut bricks = vec![];
+  for row in 0..ROWS {
+    for column in 0..COLUMNS {
+      let min = vec2(column as f32 * BRICK_SIZE.x, row as f32 * BRICK_SIZE.y);
+      bricks.push((min, min + BRICK_SIZE));
+    }
+  }
+  let destroyed = vec![false; bricks.len()];
+  Board { bricks, destroyed }
+}
+
+// 每次查询的范围相当于一个半径 12.5 的球在一个模拟步内扫过的区域
+fn queries() -> Vec<(Vector2<f32>, Vector2<f32>)> {
+  let mut rng = Pcg32::seed_from_u64(1);
+  let size = vec2(COLUMNS as f32 * BRICK_SIZE.x, ROWS as f32 * BRICK_SIZE.y);
+  (0..QUERIES).map(|_| {
+    let min = vec2(rng.gen_range(0.0, size.x), rng.gen_range(0.0, size.y));
+    (min, min + vec2(30.0, 30.0))
+  }).collect()
+}
+
+fn overlaps(a: &(Vector2<f32>, Vector2<f32>), min: Vector2<f32>, max: Vector2<f32>) -> bool {
+  a.0.x <= max.x && a.1.x >= min.x && a.0.y <= max.y && a.1.y >= min.y
+}
+
+fn brute_force(board: &Board, queries: &[(Vector2<f32>, Vector2<f32>)]) -> usize {
+  let mut found = 0;
+  for &(min, max) in queries {
+    for (idx, brick) in board.bricks.iter().enumerate() {
+      if !board.destroyed[idx] && overlaps(brick, min, max) {
+        found += 1;
+      }
+    }
+  }
+  found
+}
+
+fn grid(board: &Board, grid: &Grid, queries: &[(Vector2<f32>, Vector2<f32>)]) -> usize {
+  let mut found = 0;
+  for &(min, max) in queries {
+    for idx in grid.query(min, max) {
+      if overlaps(&board.bricks[idx], min, max) {
+        found += 1;
+      }
+    }
+  }
+  found
+}
+
+fn time<F: FnMut() -> usize>(name: &str, mut f: F) -> usize {
+  let start = Instant::now();
+  let mut runs = 0;
+  let mut result = 0;
+  while runs < 3 || start.elapsed() < Duration::from_secs(1) {
+    result = f();
+    runs += 1;
+  }
+  let per_query = start.elapsed() / (runs * QUERIES as u32);
+  println!("{:<24} {:>10.2?} per query ({} runs)", name, per_query, runs);
+  result
+}
+
+fn main() {
+  let mut board = board();
+  let queries = queries();
+
+  let start = Instant::now();
+  let mut index = Grid::new(vec2(COLUMNS as f32 * BRICK_SIZE.x, ROWS as f32 * BRICK_SIZE.y), BRICK_SIZE);
+  for (idx, &(min, max)) in board.bricks.iter().enumerate() {
+    index.insert(idx, min, max);
+  }
+  println!("{:<24} {:>10.2?} for {} bricks", "grid build", start.elapsed(), board.bricks.len());
+
+  let expected = time("brute force", || brute_force(&board, &queries));
+  let found = time("grid", || grid(&board, &index, &queries));
+  assert_eq!(expected, found, "grid and brute force disagree");
+
+  // 打碎一半砖块后再比较，网格需要增量更新
+  let start = Instant::now();
+  for idx in (0..board.bricks.len()).step_by(2) {
+    board.destroyed[idx] = true;
+    index.remove(idx);
+  }
+  println!("{:<24} {:>10.2?} for {} bricks", "grid remove", start.elapsed(), board.bricks.len() / 2);
+
+  let expected = time("brute force, half gone", || brute_force(&board, &queries));
+  let found = time("grid, half gone", || grid(&board, &index, &queries));
+  assert_eq!(expected, found, "grid and brute force disagree");
+}
@@ -0,0 +1,121 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use crate::texture_data::{TextureData, TextureError};
+use crate::texture_options::{self, ManifestError, TextureOptions};
+
+// 编译进程序的资源，key 是相对 src/ 的路径
+static EMBEDDED: [(&str, &[u8]); 8] = [
+  ("res/bg.jpg", include_bytes!("res/bg.jpg")),
+  ("res/face.png", include_bytes!("res/face.png")),
+  ("res/block.png", include_bytes!("res/block.png")),
+  ("res/block_solid.png", include_bytes!("res/block_solid.png")),
+  ("res/paddle.png", include_bytes!("res/paddle.png")),
+  ("sprite.vs", include_bytes!("sprite.vs")),
+  ("sprite.fs", include_bytes!("sprite.fs")),
+  ("textures.txt", include_bytes!("textures.txt")),
+];
+
+/// Asset key of the texture manifest, see `texture_options::parse_manifest`.
+pub const MANIFEST: &str = "textures.txt";
+
+/// The copy of asset `key` built into the binary, if there is one.
+pub fn embedded(key: &str) -> Option<&'static [u8]> {
+  EMBEDDED.iter().find(|&&(k, _)| k == key).map(|&(_, bytes)| bytes)
+}
+
+/// Where the game's images and shaders come from. They are compiled in, so the
+/// game runs from any directory; for modding, `dir` can name a directory laid
+/// out like `src/` whose files are used instead. How each texture is sampled
+/// comes from the texture manifest, `textures.txt`.
+#[derive(Clone)]
+pub struct Assets {
+  pub dir: Option<PathBuf>,
+  manifest: HashMap<String, TextureOptions>,
+}
+
+impl Default for Assets {
+  /// Just the embedded assets.
+  fn default() -> Assets {
+    Assets::new(None).unwrap_or_else(|e| panic!("Built-in texture manifest is invalid: {}", e))
+  }
+}
+
+impl Assets {
+  pub fn new(dir: Option<PathBuf>) -> Result<Assets, ManifestError> {
+    let mut assets = Assets { dir, manifest: HashMap::new() };
+    let text = assets.read_to_string(MANIFEST).map_err(|e| ManifestError::Io(MANIFEST.to_string(), e))?;
+    assets.manifest = texture_options::parse_manifest(MANIFEST, &text)?;
+    Ok(assets)
+  }
+
+  /// The bytes of asset `key`, e.g. `res/face.png`: the override directory's
+  /// file if it has one, otherwise the embedded copy.
+  pub fn read(&self, key: &str) -> io::Result<Cow<'static, [u8]>> {
+    if let Some(dir) = &self.dir {
+      let path = dir.join(key);
+      if path.is_file() {
+        return fs::read(path).map(Cow::Owned);
+      }
+    }
+    embedded(key)
+      .map(Cow::Borrowed)
+      .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("no asset named `{}`", key)))
+  }
+
+  /// Like `read`, for text assets such as shader sources.
+  pub fn read_to_string(&self, key: &str) -> io::Result<String> {
+    let bytes = self.read(key)?.into_owned();
+    String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+  }
+
+  /// Keys of every image asset: the embedded ones plus any files under the
+  /// override directory's `res/`, sorted.
+  pub fn images(&self) -> Vec<String> {
+    let mut keys: Vec<String> = EMBEDDED.iter()
+      .map(|&(key, _)| key)
+      .filter(|key| key.starts_with("res/"))
+      .map(String::from)
+      .collect();
+    if let Some(dir) = &self.dir {
+      if let Ok(entries) = fs::read_dir(dir.join("res")) {
+        for entry in entries.filter_map(Result::ok) {
+          if entry.path().is_file() {
+            keys.push(format!("res/{}", entry.file_name().to_string_lossy()));
+          }
+        }
+      }
+    }
+    keys.sort();
+    keys.dedup();
+    keys
+  }
+
+  /// The manifest's options for texture `key`, or the defaults if it isn't listed.
+  pub fn texture_options(&self, key: &str) -> TextureOptions {
+    self.manifest.get(key).cloned().unwrap_or_default()
+  }
+
+  /// Reads and decodes image asset `key`, as sRGB or premultiplied if its options say so.
+  pub fn texture(&self, key: &str) -> Result<TextureData, TextureError> {
+    let bytes = self.read(key).map_err(|e| TextureError::Io(key.to_string(), e))?;
+    let mut texture = TextureData::from_bytes(&bytes, key)?;
+    let options = self.texture_options(key);
+    texture.srgb = options.srgb;
+    if options.premultiplied {
+      texture.premultiply();
+    }
+    Ok(texture)
+  }
+
+  /// Like `texture`, but a failure is reported on stderr and drawn as
+  /// `TextureData::missing()` instead of stopping the game.
+  pub fn texture_or_missing(&self, key: &str) -> TextureData {
+    self.texture(key).unwrap_or_else(|e| {
+      eprintln!("Failed to load texture {}", e);
+      TextureData::missing()
+    })
+  }
+}
@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+use cgmath::vec2;
+use image::{Rgba, RgbaImage};
+use crate::assets::Assets;
+use crate::renderer::UvRect;
+use crate::text;
+use crate::texture_options::TextureOptions;
+
+// 每个区域四周留出的像素，用边缘像素填充，线性过滤时不会采到相邻的图
+const PADDING: u32 = 1;
+
+/// Where one packed image sits in the atlas, in pixels.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Region {
+  pub x: u32,
+  pub y: u32,
+  pub width: u32,
+  pub height: u32,
+}
+
+/// Many images packed into one, so sprites using any of them share a single
+/// texture bind. Each image is looked up by name.
+pub struct Atlas {
+  image: RgbaImage,
+  regions: HashMap<String, Region>,
+}
+
+impl Atlas {
+  /// Packs `images` in rows from tallest to shortest (shelf packing).
+  /// Each image is surrounded by a copy of its own edge pixels.
+  pub fn pack(mut images: Vec<(String, RgbaImage)>) -> Atlas {
+    images.retain(|(_, img)| img.width() > 0 && img.height() > 0);
+    images.sort_by(|a, b| b.1.height().cmp(&a.1.height()).then_with(|| a.0.cmp(&b.0)));
+    let padded = |img: &RgbaImage| (img.width() + 2 * PADDING, img.height() + 2 * PADDING);
+
+    // 先按总面积估一个正方形的边长，宽度至少放得下最宽的图
+    let area: u32 = images.iter().map(|(_, img)| padded(img).0 * padded(img).1).sum();
+    let widest = images.iter().map(|(_, img)| padded(img).0).max().unwrap_or(1);
+    let width = ((area as f32).sqrt().ceil() as u32).max(widest).next_power_of_two();
+
+    let mut positions = vec![];
+    let (mut x, mut y, mut shelf_height) = (0, 0, 0);
+    for (_, img) in &images {
+      let (w, h) = padded(img);
+      if x + w > width {
+        x = 0;
+        y += shelf_height;
+        shelf_height = 0;
+      }
+      positions.push((x, y));
+      x += w;
+      shelf_height = shelf_height.max(h);
+    }
+
+    let mut atlas = Atlas {
+      image: RgbaImage::new(width, (y + shelf_height).max(1)),
+      regions: HashMap::new(),
+    };
+    for ((name, img), (x, y)) in images.into_iter().zip(positions) {
+      let (w, h) = padded(&img);
+      for py in 0..h {
+        for px in 0..w {
+          let sx = (px.max(PADDING) - PADDING).min(img.width() - 1);
+          let sy = (py.max(PADDING) - PADDING).min(img.height() - 1);
+          atlas.image.put_pixel(x + px, y + py, *img.get_pixel(sx, sy));
+        }
+      }
+      let region = Region { x: x + PADDING, y: y + PADDING, width: img.width(), height: img.height() };
+      atlas.regions.insert(name, region);
+    }
+    atlas
+  }
+
+  /// Packs every image asset whose options are all defaults, plus a white texel
+  /// for `text::TEXTURE`. Images with their own options in the manifest keep
+  /// their own texture, since everything in the atlas is sampled the same way.
+  pub fn from_assets(assets: &Assets) -> Atlas {
+    let mut images: Vec<_> = assets.images().into_iter()
+      .filter(|key| assets.texture_options(key) == TextureOptions::new())
+      .map(|key| {
+        let img = assets.texture_or_missing(&key).to_rgba();
+        (key, img)
+      })
+      .collect();
+    images.push((String::from(text::TEXTURE), RgbaImage::from_pixel(1, 1, Rgba([255, 255, 255, 255]))));
+    Self::pack(images)
+  }
+
+  /// The packed pixels.
+  pub fn image(&self) -> &RgbaImage {
+    &self.image
+  }
+
+  pub fn region(&self, name: &str) -> Option<Region> {
+    self.regions.get(name).cloned()
+  }
+
+  /// Texture coordinates of image `name` within the atlas.
+  pub fn uv(&self, name: &str) -> Option<UvRect> {
+    let region = self.region(name)?;
+    let (width, height) = (self.image.width() as f32, self.image.height() as f32);
+    Some(UvRect {
+      min: vec2(region.x as f32 / width, region.y as f32 / height),
+      max: vec2((region.x + region.width) as f32 / width, (region.y + region.height) as f32 / height),
+    })
+  }
+
+  /// Stops drawing `name` from the atlas, e.g. when it's replaced by a texture of its own.
+  /// Its pixels stay where they are.
+  pub fn remove(&mut self, name: &str) -> Option<Region> {
+    self.regions.remove(name)
+  }
+}
@@ -0,0 +1,311 @@
+//! Text-mode frontend: plays the same `World` in a terminal, two pixels per
+//! character cell using `▀` with 24-bit colours. Needs a Unix terminal with
+//! `stty`, e.g. over SSH.
+
+use std::env;
+use std::io::{self, Read, Write};
+use std::process::{self, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+use cgmath::{vec3, Vector2, Vector3};
+use breakout::{WIDTH, HEIGHT};
+use breakout::campaign::Campaign;
+use breakout::game_object::GameObject;
+use breakout::options::{self, Options};
+use breakout::state::{State, Event};
+use breakout::timestep::FixedTimestep;
+use breakout::world::{World, Input};
+
+const SIMULATION_STEP: f32 = 1.0 / 120.0;
+const MAX_STEPS_PER_FRAME: u32 = 8;
+const FRAME_TIME: Duration = Duration::from_millis(33);
+// 每隔多少帧重新读取一次终端大小
+const RESIZE_CHECK_FRAMES: u32 = 30;
+// 终端没有按键松开事件，按下后在这段时间内都算按住，靠键盘自动重复续上
+const HOLD_TIME: Duration = Duration::from_millis(150);
+
+#[derive(Clone, Copy, PartialEq)]
+enum Key {
+  Left,
+  Right,
+  Launch,
+  Confirm,
+  Pause,
+  Back,
+  Quit,
+}
+
+/// Puts the terminal into raw mode and restores it when dropped, even on panic.
+struct RawTerminal {
+  saved: String,
+}
+
+impl RawTerminal {
+  fn new() -> io::Result<RawTerminal> {
+    let saved = stty(&["-g"])?;
+    stty(&["raw", "-echo"])?;
+    // 切到备用屏幕并隐藏光标
+    print!("\x1b[?1049h\x1b[?25l");
+    io::stdout().flush()?;
+    Ok(RawTerminal { saved: saved.trim().to_string() })
+  }
+
+  /// Terminal size as (columns, rows).
+  fn size(&self) -> (usize, usize) {
+    let size = stty(&["size"]).unwrap_or_default();
+    let mut parts = size.split_whitespace().map(|n| n.parse().unwrap_or(0));
+    let rows = parts.next().unwrap_or(24);
+    let columns = parts.next().unwrap_or(80);
+    (columns.max(20), rows.max(10))
+  }
+}
+
+impl Drop for RawTerminal {
+  fn drop(&mut self) {
+    print!("\x1b[0m\x1b[?25h\x1b[?1049l");
+    let _ = io::stdout().flush();
+    let _ = stty(&[&self.saved[..]]);
+  }
+}
+
+fn stty(args: &[&str]) -> io::Result<String> {
+  let output = Command::new("stty").args(args).stdin(Stdio::inherit()).output()?;
+  if !output.status.success() {
+    return Err(io::Error::new(io::ErrorKind::Other, "stty failed, is stdin a terminal?"));
+  }
+  Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Reads stdin on its own thread so the game loop never blocks on it.
+fn spawn_reader() -> Receiver<Vec<u8>> {
+  let (tx, rx) = mpsc::channel();
+  thread::spawn(move || {
+    let mut buf = [0; 64];
+    let stdin = io::stdin();
+    let mut stdin = stdin.lock();
+    while let Ok(n) = stdin.read(&mut buf) {
+      if n == 0 || tx.send(buf[..n].to_vec()).is_err() {
+        break;
+      }
+    }
+  });
+  rx
+}
+
+fn parse_keys(bytes: &[u8]) -> Vec<Key> {
+  let mut keys = vec![];
+  let mut idx = 0;
+  while idx < bytes.len() {
+    let key = match bytes[idx] {
+      // 方向键：ESC [ C / ESC [ D
+      0x1b if bytes.get(idx + 1) == Some(&b'[') => {
+        idx += 2;
+        match bytes.get(idx) {
+          Some(b'D') => Some(Key::Left),
+          Some(b'C') => Some(Key::Right),
+          _ => None,
+        }
+      },
+      0x1b => Some(Key::Back),
+      b'a' | b'A' => Some(Key::Left),
+      b'd' | b'D' => Some(Key::Right),
+      b' ' => Some(Key::Launch),
+      b'\r' | b'\n' => Some(Key::Confirm),
+      b'p' | b'P' => Some(Key::Pause),
+      b'q' | b'Q' | 0x03 => Some(Key::Quit),
+      _ => None,
+    };
+    keys.extend(key);
+    idx += 1;
+  }
+  keys
+}
+
+struct Term {
+  world: World,
+  state: State,
+  held: Vec<(Key, Instant)>,
+  timestep: FixedTimestep,
+}
+
+impl Term {
+  fn is_held(&self, key: Key, now: Instant) -> bool {
+    self.held.iter().any(|&(k, until)| k == key && until > now)
+  }
+
+  fn press(&mut self, key: Key, now: Instant) -> bool {
+    let event = match key {
+      Key::Left | Key::Right | Key::Launch => {
+        self.held.retain(|&(k, _)| k != key);
+        self.held.push((key, now + HOLD_TIME));
+        return true;
+      },
+      Key::Quit => return false,
+      Key::Confirm => Event::Confirm,
+      Key::Pause => Event::Pause,
+      Key::Back => Event::Back,
+    };
+    if self.state.quits_on(event) {
+      return false;
+    }
+    // 终端版没有排行榜，不需要处理状态切换的结果
+    self.state.handle(event, &mut self.world);
+    true
+  }
+
+  fn update(&mut self, dt: f32, now: Instant) {
+    let input = Input {
+      left: self.is_held(Key::Left, now),
+      right: self.is_held(Key::Right, now),
+      launch: self.is_held(Key::Launch, now),
+    };
+    self.state.update(&mut self.world, dt, input);
+  }
+
+  fn status(&self) -> String {
+    let world = &self.world;
+    let message = match self.state {
+      State::Menu => "ENTER to start, q to quit",
+      State::Playing => "a/d or arrows move, space launches, p pauses",
+      State::Paused => "paused: p resumes, esc for menu",
+      State::LevelWon => "level cleared! ENTER for the next one",
+      State::GameOver => "game over: ENTER retries, esc for menu",
+      State::CampaignComplete => "you win! ENTER for the menu",
+      State::HighScores => "ENTER for the menu",
+    };
+    format!(
+      " LEVEL {}/{}  LIVES {}  SCORE {}  X{}  | {}",
+      world.campaign.current() + 1, world.campaign.len(),
+      world.lives.remaining(), world.scoring.score(), world.scoring.multiplier(), message
+    )
+  }
+
+  /// Draws the playfield into `columns` x `rows` cells; the last row is the status line.
+  fn render(&self, columns: usize, rows: usize, alpha: f32) -> String {
+    let (width, height) = (columns, (rows - 1) * 2);
+    // 保持宽高比，每个半格当作一个正方形像素
+    let scale = (width as f32 / WIDTH as f32).min(height as f32 / HEIGHT as f32);
+    let mut pixels = vec![vec3(0.05, 0.07, 0.1); width * height];
+    let mut fill = |game_object: &GameObject, color: Vector3<f32>, round: bool| {
+      let position = game_object.interpolated_position(alpha) * scale;
+      let size = game_object.size * scale;
+      let center = position + size / 2.0;
+      let x_range = (position.x.max(0.0) as usize)..((position.x + size.x).ceil().max(0.0) as usize).min(width);
+      let y_range = (position.y.max(0.0) as usize)..((position.y + size.y).ceil().max(0.0) as usize).min(height);
+      for y in y_range {
+        for x in x_range.clone() {
+          if round {
+            let d = Vector2::new(x as f32 + 0.5, y as f32 + 0.5) - center;
+            let r = (size.x / 2.0).max(0.5);
+            if d.x * d.x + d.y * d.y > r * r {
+              continue;
+            }
+          }
+          pixels[y * width + x] = color;
+        }
+      }
+    };
+    if self.state.shows_world() {
+      for brick in self.world.bricks.iter().filter(|brick| !brick.is_destroyed) {
+        fill(&brick.game_object, brick.game_object.color, false);
+      }
+      for power_up in &self.world.power_ups {
+        fill(&power_up.game_object, power_up.game_object.color, false);
+      }
+      fill(&self.world.player.game_object, self.world.player.game_object.color * 0.8, false);
+      for ball in &self.world.balls {
+        fill(&ball.game_object, vec3(1.0, 0.85, 0.2), true);
+      }
+    }
+
+    let mut out = String::from("\x1b[H");
+    let rgb = |c: Vector3<f32>| {
+      let byte = |v: f32| (v.max(0.0).min(1.0) * 255.0) as u8;
+      (byte(c.x), byte(c.y), byte(c.z))
+    };
+    for row in 0..rows - 1 {
+      let mut last = None;
+      for x in 0..columns {
+        let top = rgb(pixels[(row * 2) * width + x]);
+        let bottom = rgb(pixels[(row * 2 + 1) * width + x]);
+        // 颜色不变时不重复输出转义序列
+        if last != Some((top, bottom)) {
+          out.push_str(&format!(
+            "\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m",
+            top.0, top.1, top.2, bottom.0, bottom.1, bottom.2
+          ));
+          last = Some((top, bottom));
+        }
+        out.push('▀');
+      }
+      out.push_str("\x1b[0m\r\n");
+    }
+    let status: String = self.status().chars().take(columns).collect();
+    out.push_str(&format!("\x1b[0m\x1b[K{}", status));
+    out
+  }
+}
+
+fn main() {
+  let options = Options::parse(env::args().skip(1)).unwrap_or_else(|e| {
+    eprintln!("{}\n{}", e, options::USAGE);
+    process::exit(2);
+  });
+  let campaign = Campaign::from_options(&options).unwrap_or_else(|e| {
+    eprintln!("Failed to load level: {}", e);
+    process::exit(1);
+  });
+  let terminal = RawTerminal::new().unwrap_or_else(|e| {
+    eprintln!("Failed to set up the terminal: {}", e);
+    process::exit(1);
+  });
+  let input = spawn_reader();
+  let mut term = Term {
+    world: World::new(campaign, options.lives),
+    state: State::Menu,
+    held: vec![],
+    timestep: FixedTimestep::new(SIMULATION_STEP, MAX_STEPS_PER_FRAME),
+  };
+
+  let stdout = io::stdout();
+  let mut last_time = Instant::now();
+  let mut size = terminal.size();
+  let mut frame_count = 0;
+  'game: loop {
+    let frame_start = Instant::now();
+    while let Ok(bytes) = input.try_recv() {
+      for key in parse_keys(&bytes) {
+        if !term.press(key, frame_start) {
+          break 'game;
+        }
+      }
+    }
+
+    let steps = term.timestep.advance((frame_start - last_time).as_secs_f32());
+    last_time = frame_start;
+    for _ in 0..steps {
+      term.update(term.timestep.step(), frame_start);
+    }
+
+    frame_count += 1;
+    let mut frame = String::new();
+    if frame_count % RESIZE_CHECK_FRAMES == 0 {
+      let new_size = terminal.size();
+      if new_size != size {
+        frame.push_str("\x1b[2J");
+        size = new_size;
+      }
+    }
+    frame.push_str(&term.render(size.0, size.1, term.timestep.alpha()));
+    let mut out = stdout.lock();
+    if out.write_all(frame.as_bytes()).and_then(|_| out.flush()).is_err() {
+      break;
+    }
+
+    if let Some(rest) = FRAME_TIME.checked_sub(frame_start.elapsed()) {
+      thread::sleep(rest);
+    }
+  }
+  drop(terminal);
+}
@@ -0,0 +1,159 @@
+use cgmath::{vec2, Vector2};
+
+/// Uniform grid over the playfield that buckets objects by the cells their bounds
+/// cover, so a query only looks at objects near the area asked about.
+/// Objects are named by their index in the caller's own list.
+pub struct Grid {
+  cell_size: Vector2<f32>,
+  columns: usize,
+  rows: usize,
+  cells: Vec<Vec<usize>>,
+  // 每个物体当前占用的格子范围，已移除的为 None
+  spans: Vec<Option<Span>>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct Span {
+  min_column: usize,
+  min_row: usize,
+  max_column: usize,
+  max_row: usize,
+}
+
+impl Grid {
+  /// An empty grid covering `0..size` with cells of `cell_size`.
+  pub fn new(size: Vector2<f32>, cell_size: Vector2<f32>) -> Grid {
+    let columns = ((size.x / cell_size.x).ceil() as usize).max(1);
+    let rows = ((size.y / cell_size.y).ceil() as usize).max(1);
+    Grid {
+      cell_size,
+      columns,
+      rows,
+      cells: vec![vec![]; columns * rows],
+      spans: vec![],
+    }
+  }
+
+  /// Adds object `idx` with bounds `min`..`max`, or moves it there if it's already in.
+  pub fn insert(&mut self, idx: usize, min: Vector2<f32>, max: Vector2<f32>) {
+    let span = self.span(min, max);
+    if idx >= self.spans.len() {
+      self.spans.resize(idx + 1, None);
+    }
+    match self.spans[idx] {
+      Some(old) if old == span => return,
+      Some(_) => self.remove(idx),
+      None => {},
+    }
+    for row in span.min_row..=span.max_row {
+      for column in span.min_column..=span.max_column {
+        self.cells[row * self.columns + column].push(idx);
+      }
+    }
+    self.spans[idx] = Some(span);
+  }
+
+  /// Takes object `idx` out of the grid, e.g. when its brick is destroyed.
+  pub fn remove(&mut self, idx: usize) {
+    let span = match self.spans.get_mut(idx).and_then(Option::take) {
+      Some(span) => span,
+      None => return,
+    };
+    for row in span.min_row..=span.max_row {
+      for column in span.min_column..=span.max_column {
+        let cell = &mut self.cells[row * self.columns + column];
+        if let Some(pos) = cell.iter().position(|&i| i == idx) {
+          cell.swap_remove(pos);
+        }
+      }
+    }
+  }
+
+  /// Indices of every object whose cells overlap `min`..`max`, in ascending order.
+  /// This is conservative: callers still run the exact test on what comes back.
+  pub fn query(&self, min: Vector2<f32>, max: Vector2<f32>) -> Vec<usize> {
+    let span = self.span(min, max);
+    let mut found = vec![];
+    for row in span.min_row..=span.max_row {
+      for column in span.min_column..=span.max_column {
+        found.extend_from_slice(&self.cells[row * self.columns + column]);
+      }
+    }
+    found.sort_unstable();
+    found.dedup();
+    found
+  }
+
+  // 超出网格的部分归到边缘的格子里
+  fn span(&self, min: Vector2<f32>, max: Vector2<f32>) -> Span {
+    let cell = |p: Vector2<f32>| {
+      let c = vec2(p.x / self.cell_size.x, p.y / self.cell_size.y);
+      (
+        (c.x.max(0.0) as usize).min(self.columns - 1),
+        (c.y.max(0.0) as usize).min(self.rows - 1),
+      )
+    };
+    let (min_column, min_row) = cell(min);
+    let (max_column, max_row) = cell(max);
+    Span { min_column, min_row, max_column, max_row }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// A 100x100 grid of 10x10 cells holding three 10x10 objects along the top row.
+  fn grid() -> Grid {
+    let mut grid = Grid::new(vec2(100.0, 100.0), vec2(10.0, 10.0));
+    for idx in 0..3 {
+      let min = vec2(idx as f32 * 30.0 + 1.0, 1.0);
+      grid.insert(idx, min, min + vec2(8.0, 8.0));
+    }
+    grid
+  }
+
+  #[test]
+  fn query_finds_objects_in_overlapping_cells() {
+    let grid = grid();
+    assert_eq!(grid.query(vec2(0.0, 0.0), vec2(100.0, 100.0)), vec![0, 1, 2]);
+    assert_eq!(grid.query(vec2(32.0, 2.0), vec2(35.0, 5.0)), vec![1]);
+    assert_eq!(grid.query(vec2(50.0, 50.0), vec2(60.0, 60.0)), Vec::<usize>::new());
+  }
+
+  #[test]
+  fn removed_objects_are_not_found() {
+    let mut grid = grid();
+    grid.remove(1);
+    assert_eq!(grid.query(vec2(0.0, 0.0), vec2(100.0, 100.0)), vec![0, 2]);
+    assert_eq!(grid.query(vec2(32.0, 2.0), vec2(35.0, 5.0)), Vec::<usize>::new());
+    // 重复移除或移除不存在的物体什么都不做
+    grid.remove(1);
+    grid.remove(42);
+    assert_eq!(grid.query(vec2(0.0, 0.0), vec2(100.0, 100.0)), vec![0, 2]);
+  }
+
+  #[test]
+  fn moved_objects_are_only_found_where_they_are() {
+    let mut grid = grid();
+    grid.insert(0, vec2(75.0, 75.0), vec2(95.0, 95.0));
+    assert_eq!(grid.query(vec2(1.0, 1.0), vec2(9.0, 9.0)), Vec::<usize>::new());
+    assert_eq!(grid.query(vec2(80.0, 80.0), vec2(85.0, 85.0)), vec![0]);
+    assert_eq!(grid.query(vec2(0.0, 0.0), vec2(100.0, 100.0)), vec![0, 1, 2]);
+
+    // 移除后再插入，回到原来的位置
+    grid.remove(0);
+    grid.insert(0, vec2(1.0, 1.0), vec2(9.0, 9.0));
+    assert_eq!(grid.query(vec2(80.0, 80.0), vec2(85.0, 85.0)), Vec::<usize>::new());
+    assert_eq!(grid.query(vec2(1.0, 1.0), vec2(9.0, 9.0)), vec![0]);
+  }
+
+  #[test]
+  fn bounds_outside_the_grid_go_to_the_edge_cells() {
+    let mut grid = Grid::new(vec2(100.0, 100.0), vec2(10.0, 10.0));
+    grid.insert(0, vec2(-20.0, -20.0), vec2(-5.0, -5.0));
+    grid.insert(1, vec2(120.0, 50.0), vec2(130.0, 55.0));
+    assert_eq!(grid.query(vec2(0.0, 0.0), vec2(1.0, 1.0)), vec![0]);
+    assert_eq!(grid.query(vec2(99.0, 50.0), vec2(99.0, 50.0)), vec![1]);
+  }
+}
@@ -0,0 +1,84 @@
+use rand::SeedableRng;
+use rand_pcg::Pcg32;
+use crate::level::{Level, LevelError};
+use crate::game_object::Brick;
+use crate::options::Options;
+
+/// An ordered list of levels played one after another.
+pub struct Campaign {
+  levels: Vec<Level>,
+  current: usize,
+  seed: Option<u64>,
+}
+
+impl Campaign {
+  pub fn new(levels: Vec<Level>) -> Campaign {
+    assert!(!levels.is_empty(), "campaign needs at least one level");
+    Campaign {
+      levels,
+      current: 0,
+      seed: None,
+    }
+  }
+
+  /// Fallback when no level files are given. Every level is drawn from one
+  /// generator, so the seed alone reproduces the whole campaign.
+  pub fn random(count: usize, width: usize, height: usize, seed: u64) -> Campaign {
+    let mut rng = Pcg32::seed_from_u64(seed);
+    let levels = (0..count).map(|_| Level::random(width, height, &mut rng)).collect();
+    Campaign {
+      seed: Some(seed),
+      ..Self::new(levels)
+    }
+  }
+
+  /// The campaign the command line asks for: its level files in order, or random
+  /// levels when there are none.
+  pub fn from_options(options: &Options) -> Result<Campaign, LevelError> {
+    if options.levels.is_empty() {
+      return Ok(Self::random(3, 12, 6, options.seed.unwrap_or_else(rand::random)));
+    }
+    let levels = options.levels.iter().map(|path| Level::load(path)).collect::<Result<_, _>>()?;
+    Ok(Self::new(levels))
+  }
+
+  /// Seed the levels were generated from, `None` for level files.
+  pub fn seed(&self) -> Option<u64> {
+    self.seed
+  }
+
+  pub fn level(&self) -> &Level {
+    &self.levels[self.current]
+  }
+
+  /// 0-based index of the level being played.
+  pub fn current(&self) -> usize {
+    self.current
+  }
+
+  /// Number of levels. Never zero, `new` refuses a campaign without levels.
+  #[allow(clippy::len_without_is_empty)]
+  pub fn len(&self) -> usize {
+    self.levels.len()
+  }
+
+  /// Moves on to the next level. Returns `false` when the last level has been
+  /// cleared, i.e. the campaign is complete.
+  pub fn advance(&mut self) -> bool {
+    if self.current + 1 < self.levels.len() {
+      self.current += 1;
+      true
+    } else {
+      false
+    }
+  }
+
+  pub fn restart(&mut self) {
+    self.current = 0;
+  }
+
+  /// A level is cleared once every breakable brick is destroyed; solid bricks don't count.
+  pub fn is_level_cleared(bricks: &[Brick]) -> bool {
+    bricks.iter().all(|brick| brick.is_solid || brick.is_destroyed)
+  }
+}
@@ -0,0 +1,263 @@
+use cgmath::{vec2, InnerSpace, Vector2};
+
+/// Earliest contact of a moving circle within one motion.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SweepHit {
+  /// Fraction of the motion travelled before touching, in 0..=1.
+  pub time: f32,
+  /// Unit surface normal at the contact, pointing towards the circle.
+  pub normal: Vector2<f32>,
+}
+
+/// Identifies one of the objects taking part in a contact.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ObjectId {
+  /// Index into the balls in play.
+  Ball(usize),
+  /// The brick's unique id, see `Brick::id`.
+  Brick(u32),
+  Paddle,
+}
+
+/// Two overlapping objects: circle `a` and circle or box `b`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Contact {
+  pub a: ObjectId,
+  pub b: ObjectId,
+  /// Unit direction to push `a` out along, pointing away from `b`.
+  pub normal: Vector2<f32>,
+  /// Point on `b`'s surface closest to `a`'s centre.
+  pub point: Vector2<f32>,
+  /// How far `a` has to move along `normal` to stop overlapping.
+  pub penetration: f32,
+}
+
+/// Contact between circle `a` of `radius` at `center` and box `b` spanning `min`..`max`,
+/// or `None` if they don't overlap. Corner hits get the true diagonal normal.
+pub fn circle_aabb_contact(a: ObjectId, center: Vector2<f32>, radius: f32, b: ObjectId, min: Vector2<f32>, max: Vector2<f32>) -> Option<Contact> {
+  let closest = vec2(center.x.max(min.x).min(max.x), center.y.max(min.y).min(max.y));
+  let diff = center - closest;
+  let distance = diff.magnitude();
+  if distance > 0.0 {
+    if distance >= radius {
+      return None;
+    }
+    return Some(Contact { a, b, normal: diff / distance, point: closest, penetration: radius - distance });
+  }
+  // 圆心已在矩形内部：沿最近的边推出去
+  let faces = [
+    (center.x - min.x, vec2(-1.0, 0.0), vec2(min.x, center.y)),
+    (max.x - center.x, vec2(1.0, 0.0), vec2(max.x, center.y)),
+    (center.y - min.y, vec2(0.0, -1.0), vec2(center.x, min.y)),
+    (max.y - center.y, vec2(0.0, 1.0), vec2(center.x, max.y)),
+  ];
+  let (depth, normal, point) = faces.iter().fold(faces[0], |best, &face| if face.0 < best.0 { face } else { best });
+  Some(Contact { a, b, normal, point, penetration: depth + radius })
+}
+
+/// Contact between circle `a` and circle `b`, or `None` if they don't overlap
+/// or share a centre (no normal to push along).
+pub fn circle_circle_contact(a: ObjectId, center_a: Vector2<f32>, radius_a: f32, b: ObjectId, center_b: Vector2<f32>, radius_b: f32) -> Option<Contact> {
+  let diff = center_a - center_b;
+  let distance = diff.magnitude();
+  let penetration = radius_a + radius_b - distance;
+  if penetration <= 0.0 || distance == 0.0 {
+    return None;
+  }
+  let normal = diff / distance;
+  Some(Contact { a, b, normal, point: center_b + normal * radius_b, penetration })
+}
+
+/// Merges every contact of one circle into a single push: the normals are averaged
+/// and the deepest penetration is kept, returned as `(normal, penetration)`. Hitting
+/// the seam between two bricks then gives the same single bounce as hitting one
+/// brick, and an inside corner bounces straight back.
+pub fn combine_contacts(contacts: &[Contact]) -> Option<(Vector2<f32>, f32)> {
+  let sum = contacts.iter().fold(vec2(0.0, 0.0), |sum, contact| sum + contact.normal);
+  let penetration = contacts.iter().fold(0.0f32, |deepest, contact| deepest.max(contact.penetration));
+  if sum.magnitude2() < std::f32::EPSILON {
+    // 法线相互抵消（被夹在中间），退回到最深的那个接触
+    return contacts.iter().fold(None, |deepest: Option<&Contact>, contact| match deepest {
+      Some(d) if d.penetration >= contact.penetration => Some(d),
+      _ => Some(contact),
+    }).map(|contact| (contact.normal, contact.penetration));
+  }
+  Some((sum.normalize(), penetration))
+}
+
+/// Sweeps a circle of `radius` at `center` along `motion` against the box `min`..`max`.
+///
+/// This is a ray cast against the box grown by `radius` with rounded corners
+/// (its Minkowski sum with the circle). A circle already overlapping the box,
+/// or moving away from it, gives `None`; overlaps are left to the contact phase.
+pub fn sweep_circle_aabb(center: Vector2<f32>, radius: f32, motion: Vector2<f32>, min: Vector2<f32>, max: Vector2<f32>) -> Option<SweepHit> {
+  let grown_min = min - vec2(radius, radius);
+  let grown_max = max + vec2(radius, radius);
+
+  let mut t_enter = std::f32::NEG_INFINITY;
+  let mut t_exit = std::f32::INFINITY;
+  let mut normal = vec2(0.0, 0.0);
+  for axis in 0..2 {
+    if motion[axis].abs() < std::f32::EPSILON {
+      if center[axis] < grown_min[axis] || center[axis] > grown_max[axis] {
+        return None;
+      }
+      continue;
+    }
+    let mut t1 = (grown_min[axis] - center[axis]) / motion[axis];
+    let mut t2 = (grown_max[axis] - center[axis]) / motion[axis];
+    if t1 > t2 {
+      std::mem::swap(&mut t1, &mut t2);
+    }
+    if t1 > t_enter {
+      t_enter = t1;
+      normal = vec2(0.0, 0.0);
+      normal[axis] = -motion[axis].signum();
+    }
+    t_exit = t_exit.min(t2);
+    if t_enter > t_exit {
+      return None;
+    }
+  }
+  if !(0.0..=1.0).contains(&t_enter) {
+    return None;
+  }
+
+  // 命中点在原矩形两个轴向之外时，落在圆角上，需要再和角上的圆求交
+  let point = center + motion * t_enter;
+  let corner_x = if point.x < min.x { Some(min.x) } else if point.x > max.x { Some(max.x) } else { None };
+  let corner_y = if point.y < min.y { Some(min.y) } else if point.y > max.y { Some(max.y) } else { None };
+  match (corner_x, corner_y) {
+    (Some(x), Some(y)) => sweep_circle_point(center, radius, motion, vec2(x, y)),
+    _ => Some(SweepHit { time: t_enter, normal }),
+  }
+}
+
+/// Sweeps a circle against a single point, i.e. a ray against a circle around the point.
+fn sweep_circle_point(center: Vector2<f32>, radius: f32, motion: Vector2<f32>, point: Vector2<f32>) -> Option<SweepHit> {
+  let m = center - point;
+  let a = motion.dot(motion);
+  let b = 2.0 * m.dot(motion);
+  let c = m.dot(m) - radius * radius;
+  if c < 0.0 || a == 0.0 {
+    return None;
+  }
+  let discriminant = b * b - 4.0 * a * c;
+  if discriminant < 0.0 {
+    return None;
+  }
+  let time = (-b - discriminant.sqrt()) / (2.0 * a);
+  if !(0.0..=1.0).contains(&time) {
+    return None;
+  }
+  let normal = (center + motion * time - point).normalize();
+  Some(SweepHit { time, normal })
+}
+
+/// Sweeps a circle against the left, top and right edges of a `width` wide playfield.
+/// The bottom is open.
+pub fn sweep_circle_walls(center: Vector2<f32>, radius: f32, motion: Vector2<f32>, width: f32) -> Option<SweepHit> {
+  let mut earliest: Option<SweepHit> = None;
+  let mut consider = |distance: f32, speed: f32, normal: Vector2<f32>| {
+    // distance: 圆心到墙的剩余距离，speed: 沿墙法线反方向的位移
+    if speed <= 0.0 || distance > speed {
+      return;
+    }
+    let time = (distance / speed).max(0.0);
+    if earliest.map_or(true, |hit| time < hit.time) {
+      earliest = Some(SweepHit { time, normal });
+    }
+  };
+  consider(center.x - radius, -motion.x, vec2(1.0, 0.0));
+  consider(width - radius - center.x, motion.x, vec2(-1.0, 0.0));
+  consider(center.y - radius, -motion.y, vec2(0.0, 1.0));
+  earliest
+}
+
+/// Bounces `velocity` off a surface with unit `normal`, unless it is already moving away from it.
+pub fn bounce(velocity: Vector2<f32>, normal: Vector2<f32>) -> Vector2<f32> {
+  if velocity.dot(normal) < 0.0 {
+    reflect(velocity, normal)
+  } else {
+    velocity
+  }
+}
+
+/// Mirrors `velocity` about a surface with unit `normal`.
+pub fn reflect(velocity: Vector2<f32>, normal: Vector2<f32>) -> Vector2<f32> {
+  velocity - normal * (2.0 * velocity.dot(normal))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const RADIUS: f32 = 12.5;
+
+  fn assert_close(actual: f32, expected: f32, what: &str) {
+    assert!((actual - expected).abs() < 0.5, "{}: {} is not {}", what, actual, expected);
+  }
+
+  fn assert_normal(actual: Vector2<f32>, expected: Vector2<f32>, what: &str) {
+    assert!(actual.dot(expected) > 0.999, "{}: normal {:?} is not {:?}", what, actual, expected);
+  }
+
+  /// Contact of a ball at `center` with brick `id` spanning `min`..`max`.
+  fn brick_contact(center: Vector2<f32>, id: u32, min: Vector2<f32>, max: Vector2<f32>) -> Option<Contact> {
+    circle_aabb_contact(ObjectId::Ball(0), center, RADIUS, ObjectId::Brick(id), min, max)
+  }
+
+  #[test]
+  fn seam_between_two_bricks_bounces_once() {
+    let (left, right) = ((vec2(0.0, 0.0), vec2(50.0, 20.0)), (vec2(50.0, 0.0), vec2(100.0, 20.0)));
+    for &x in &[50.0, 48.0, 53.0] {
+      let center = vec2(x, 30.0);
+      let contacts: Vec<_> = vec![brick_contact(center, 1, left.0, left.1), brick_contact(center, 2, right.0, right.1)]
+        .into_iter().flatten().collect();
+      assert_eq!(contacts.len(), 2, "ball at x = {} should touch both bricks", x);
+
+      let (normal, penetration) = combine_contacts(&contacts).unwrap();
+      assert!(normal.y > 0.95, "seam at x = {} gave normal {:?}", x, normal);
+      let deepest = contacts.iter().map(|contact| contact.penetration).fold(0.0, f32::max);
+      assert_eq!(penetration, deepest);
+      assert_close(penetration, 2.5, "penetration");
+
+      // 接缝处只反弹一次：翻转两次会让球继续往上穿过砖块
+      let velocity = bounce(vec2(150.0, -500.0), normal);
+      assert!(velocity.y > 0.0, "seam at x = {} flipped the velocity {:?}", x, velocity);
+    }
+  }
+
+  #[test]
+  fn inside_corner_bounces_straight_back() {
+    // 横着的砖块和竖着的砖块围成一个内角，球从左下方斜着打进去
+    let (top, side) = ((vec2(0.0, 0.0), vec2(100.0, 20.0)), (vec2(100.0, 0.0), vec2(120.0, 100.0)));
+    let center = vec2(100.0 - RADIUS + 1.0, 20.0 + RADIUS - 1.0);
+    let contacts: Vec<_> = vec![brick_contact(center, 1, top.0, top.1), brick_contact(center, 2, side.0, side.1)]
+      .into_iter().flatten().collect();
+    assert_eq!(contacts.len(), 2);
+
+    let (normal, penetration) = combine_contacts(&contacts).unwrap();
+    assert_normal(normal, vec2(-1.0, 1.0).normalize(), "inside corner");
+    assert_close(penetration, 1.0, "penetration");
+    let velocity = bounce(vec2(300.0, -300.0), normal);
+    assert_close(velocity.x, -300.0, "velocity.x");
+    assert_close(velocity.y, 300.0, "velocity.y");
+  }
+
+  #[test]
+  fn single_contact_is_kept_as_is() {
+    let contact = brick_contact(vec2(25.0, 30.0), 1, vec2(0.0, 0.0), vec2(50.0, 20.0)).unwrap();
+    assert_eq!(combine_contacts(&[contact]), Some((contact.normal, contact.penetration)));
+    assert_eq!(combine_contacts(&[]), None);
+  }
+
+  #[test]
+  fn opposite_contacts_fall_back_to_the_deepest() {
+    // 被夹在上下两块砖中间，法线相互抵消
+    let center = vec2(25.0, 40.0);
+    let above = brick_contact(center, 1, vec2(0.0, 0.0), vec2(50.0, 30.0)).unwrap();
+    let below = brick_contact(center, 2, vec2(0.0, 48.0), vec2(50.0, 70.0)).unwrap();
+    assert_eq!(combine_contacts(&[above, below]), Some((below.normal, below.penetration)));
+  }
+}
@@ -1,318 +1,215 @@
 extern crate glfw;
 extern crate gl;
 use std::collections::HashSet;
-use std::f32;
 use self::glfw::{Context, Key, Action};
+use crate::shader::ShaderError;
 use crate::sprite::Sprite;
-use crate::game_object::{GameObject, Ball, Brick, Bg, Paddle};
-use cgmath::{vec3, vec2, InnerSpace, Vector2, Matrix2, Deg, Rad};
-use rand::prelude::*;
-use rand::distributions::WeightedIndex;
-use crate::{WIDTH, HEIGHT};
 use crate::window::{Window};
-
-pub enum Direction {
-  UP,
-  RIGHT,
-  DOWN,
-  LEFT
-}
-
-pub struct Collision (bool, Direction, f32);
-
+use breakout::assets::Assets;
+use breakout::campaign::Campaign;
+use breakout::state::{State, Event, Transition};
+use breakout::highscore::{self, HighScores, HighScore};
+use breakout::renderer::Renderer;
+use breakout::screen::Screen;
+use breakout::timestep::FixedTimestep;
+use breakout::world::{World, Input};
+use breakout::{WIDTH, HEIGHT};
+use cgmath::vec3;
+
+/// The window around a `World`: turns key presses into input and state changes,
+/// and draws the world and the menus with OpenGL.
 pub struct Game {
-  window: Window,
+  // 纹理要在窗口（GL 上下文）销毁前释放，所以 sprite 放在 window 前面
   sprite: Sprite,
+  window: Window,
   keys: HashSet<Key>,
-  
-  bg: Bg,
-  player: Paddle,
-  ball: Ball,
-  bricks: Vec<Brick>,
+  state: State,
+
+  world: World,
+  high_scores: HighScores,
+  // 上榜时输入的名字，输入结束后为 None
+  name_entry: Option<String>,
+  timestep: FixedTimestep,
 }
 
+const SIMULATION_STEP: f32 = 1.0 / 120.0;
+// 一帧最多追赶的模拟步数，超出的时间直接丢弃
+const MAX_STEPS_PER_FRAME: u32 = 8;
+
 impl Game {
-  pub fn new() -> Game {
+  pub fn new(campaign: Campaign, starting_lives: u32, assets: &Assets) -> Result<Game, ShaderError> {
     let window = Window::new();
-    let bg = Bg::new();
 
-    let (player, ball, bricks) = Self::gen_level();
-
-    Game {
-      sprite: Sprite::new(WIDTH, HEIGHT),
+    Ok(Game {
+      sprite: Sprite::new(WIDTH, HEIGHT, assets)?,
       window,
 
       keys: HashSet::new(),
-      player,
-      ball,
-      bricks,
-      bg,
-    }
+      state: State::Menu,
+      world: World::new(campaign, starting_lives),
+      high_scores: HighScores::load(HighScores::default_path()),
+      name_entry: None,
+      timestep: FixedTimestep::new(SIMULATION_STEP, MAX_STEPS_PER_FRAME),
+    })
   }
 }
 
 impl Game {
   pub fn process_events(&mut self) {
-    for (_, event) in glfw::flush_messages(&self.window.events) {
+    let events: Vec<_> = glfw::flush_messages(&self.window.events).collect();
+    for (_, event) in events {
         match event {
-            glfw::WindowEvent::Key(Key::Escape, _, Action::Press, _) => self.window.win.set_should_close(true),
             glfw::WindowEvent::Key(key, _, Action::Press, _) => {
               self.keys.insert(key);
+              self.process_key(key);
+            },
+            glfw::WindowEvent::Key(key, _, Action::Repeat, _) if self.name_entry.is_some() => {
+              self.process_name_key(key);
             },
             glfw::WindowEvent::Key(key, _, Action::Release, _) => {
               self.keys.remove(&key);
             },
+            glfw::WindowEvent::Char(c) => self.process_char(c),
             _ => {}
         }
     }
   }
 
-  pub fn run(&mut self) {
-    let mut last_time = 0.0;
-    while !self.window.win.should_close() {
-      self.process_events();
-
-      unsafe {
-        gl::ClearColor(0.2, 0.3, 0.3, 1.0);
-        gl::Clear(gl::COLOR_BUFFER_BIT);
-      }
-
-      let curr_time = self.window.glfw.get_time() as f32;
-      self.update(curr_time - last_time);
-      last_time = curr_time;
-
-      // glfw: swap buffers and poll IO events (keys pressed/released, mouse moved etc.)
-      // -------------------------------------------------------------------------------
-      self.window.win.swap_buffers();
-      self.window.glfw.poll_events();
-    }
-  }
-
-  pub fn check_collision(goa: &GameObject, gob: &GameObject) -> bool {
-    let collision_x = !(
-      goa.position[0] >= gob.position[0] + gob.size[0]
-      || goa.position[0] + goa.size[0] < gob.position[0]
-    );
-    let collision_y = !(
-      goa.position[1] >= gob.position[1] + gob.size[1]
-      || goa.position[1] + goa.size[1] < gob.position[1]
-    );
-    return collision_x && collision_y;
+  fn process_key(&mut self, key: Key) {
+    if self.name_entry.is_some() {
+      self.process_name_key(key);
+      return;
+    }
+    let event = match key {
+      Key::Enter => Event::Confirm,
+      Key::P => Event::Pause,
+      Key::Escape => Event::Back,
+      Key::H => Event::ShowHighScores,
+      _ => return,
+    };
+    if self.state.quits_on(event) {
+      self.window.win.set_should_close(true);
+      return;
+    }
+    self.transition(event);
   }
 
-  pub fn calc_vector_direction(v: Vector2<f32>) -> Direction {
-    let left = v.dot(vec2(1.0, 0.0));
-    let down = v.dot(vec2(0.0, 1.0));
-    let right = v.dot(vec2(-1.0, 0.0));
-    let up = v.dot(vec2(0.0, -1.0));
-    let max = left.max(down.max(right.max(up)));
-    if max == left {
-      Direction::LEFT
-    } else if max == down {
-      Direction::DOWN
-    } else if max == right {
-      Direction::RIGHT
-    } else {
-      Direction::UP
+  fn process_name_key(&mut self, key: Key) {
+    match key {
+      Key::Enter => self.submit_high_score(),
+      Key::Escape => self.name_entry = None,
+      Key::Backspace => {
+        if let Some(name) = &mut self.name_entry {
+          name.pop();
+        }
+      },
+      _ => {}
     }
   }
 
-  pub fn check_circle_rect_collision(circle: &GameObject, rect: &GameObject) -> Collision {
-    let circle_radius = circle.size[0] / 2.0;
-    let circle_center = circle.position + circle.size / 2.0;
-    let rect_half_extends = rect.size / 2.0;
-    let rect_center = rect.position + rect_half_extends;
-    let diff = circle_center - rect_center;
-    let clamped_diff = vec2(
-        (-rect_half_extends[0]).max(rect_half_extends[0].min(diff[0])),
-        (-rect_half_extends[1]).max(rect_half_extends[1].min(diff[1]))
-    );
-    let closest = rect_center + clamped_diff;
-
-    let is_outside = clamped_diff.dot(closest - circle_center) < 0.0;
-
-    let magnitude = (closest - circle_center).magnitude();
-    let penetration = circle_radius - magnitude;
-    Collision (
-      penetration > 0.0 && is_outside,
-      Self::calc_vector_direction(diff),
-      penetration
-    )
-  }
-
-  fn gen_level() -> (Paddle, Ball, Vec<Brick>) {
-    let mut tile_data = vec![];
-    let width = 12;
-    let height = 6;
-    let lv_width = WIDTH;
-    let lv_height = HEIGHT / 2;
-
-    let (unit_width, unit_height) = (lv_width as f32 / width as f32, lv_height as f32 / height as f32);
-
-    let choices = [1, 2, 3, 4, 5];
-    let weights = [3, 4, 4, 4, 4];
-    let dist = WeightedIndex::new(&weights).unwrap();
-
-    let mut rng = thread_rng();
-    for _ in 0..width * height {
-      tile_data.push(choices[dist.sample(&mut rng)]);
-    }
-
-    let mut bricks = vec![];
-    {
-      for (idx, t) in tile_data.iter().enumerate() {
-        let row = idx / width;
-        let column = idx % width;
-        let position = vec2(column as f32 * unit_width, row as f32 * unit_height);
-        match t {
-          1 => bricks.push(Brick::new(position, vec2(unit_width, unit_height), vec3(0.8, 0.8, 0.7), true)),
-          2 => bricks.push(Brick::new(position, vec2(unit_width, unit_height), vec3(0.2, 0.6, 1.0), false)),
-          3 => bricks.push(Brick::new(position, vec2(unit_width, unit_height), vec3(0.0, 0.7, 0.0), false)),
-          4 => bricks.push(Brick::new(position, vec2(unit_width, unit_height), vec3(0.8, 0.8, 0.4), false)),
-          5 => bricks.push(Brick::new(position, vec2(unit_width, unit_height), vec3(1.0, 0.5, 0.0), false)),
-          _ => ()
-        };
+  fn process_char(&mut self, c: char) {
+    if let Some(name) = &mut self.name_entry {
+      let c = c.to_ascii_uppercase();
+      if name.len() < highscore::MAX_NAME_LEN && (c.is_ascii_alphanumeric() || c == ' ' || c == '-') {
+        name.push(c);
       }
     }
-
-    let ball = Ball::new(vec2(750.0, 550.0));
-    let player = Paddle::new(vec2(500.0, 580.0));
-
-    (player, ball, bricks)
-  }
-}
-
-impl Game {
-  fn draw(&self, game_object: &GameObject) {
-    self.sprite.draw(&game_object.texture, game_object.position, game_object.size, 0.0, game_object.color);
   }
 
-  fn check_ball_player_collision(&mut self) {
-    let player = &self.player;
-    let ball = &mut self.ball;
-    let player_go = &player.game_object;
-    let ball_go = &mut ball.game_object;
-    if Self::check_collision(&player_go, &ball_go) {
-      ball.velocity.y = -ball.velocity.y;
-      ball_go.position.y = player_go.position.y - ball_go.size.y;
-      // 球碰撞点距离挡板中心的距离
-      let percentage = (ball_go.position.x + ball_go.size.x / 2.0 - player_go.position.x - player_go.size.x / 2.0) / (player_go.size.x / 2.0);
-      let angle = ball.velocity.angle(vec2(0.0, -1.0));
-      let mut next_angle = angle + Rad::from(Deg(20.0 * percentage));
-      if next_angle > Rad::from(Deg(45.0)) {
-        next_angle = Rad::from(Deg(45.0));
-      } else if next_angle < Rad::from(Deg(-45.0)) {
-        next_angle = Rad::from(Deg(-45.0));
-      }
-      let rotation = Matrix2::from_angle(next_angle);
-      ball.velocity = rotation * vec2(0.0, -1.0) * ball.velocity.magnitude();
+  fn submit_high_score(&mut self) {
+    let name = match self.name_entry.take() {
+      Some(name) => name,
+      None => return,
+    };
+    let name = name.trim();
+    self.high_scores.insert(HighScore {
+      name: String::from(if name.is_empty() { "PLAYER" } else { name }),
+      score: self.world.scoring.score(),
+      level: self.world.campaign.current() + 1,
+      seed: self.world.campaign.seed(),
+      date: highscore::today(),
+    });
+    if let Err(e) = self.high_scores.save() {
+      eprintln!("Failed to save high scores: {}", e);
     }
   }
 
-  fn check_ball_brick_collision(&mut self) {
-    for brick in self.bricks.iter_mut() {
-      if brick.is_destroyed {
-        continue;
-      }
-      let Collision (collided, direction, penetration) = Self::check_circle_rect_collision(&self.ball.game_object, &brick.game_object);
-      if collided {
-        if !brick.is_solid {
-          brick.is_destroyed = true;
-        }
-        match direction {
-          Direction::LEFT => {
-            self.ball.velocity.x = -self.ball.velocity.x;
-            self.ball.game_object.position.x = self.ball.game_object.position.x - penetration; 
-          },
-          Direction::RIGHT => {
-            self.ball.velocity.x = -self.ball.velocity.x;
-            self.ball.game_object.position.x = self.ball.game_object.position.x + penetration;
-          },
-          Direction::DOWN => {
-            self.ball.velocity.y = -self.ball.velocity.y;
-            self.ball.game_object.position.y = self.ball.game_object.position.y + penetration;
-          },
-          Direction::UP => {
-            self.ball.velocity.y = -self.ball.velocity.y;
-            self.ball.game_object.position.y = self.ball.game_object.position.y - penetration;
-          },
-        }
-      }
+  /// Moves to the state `event` leads to.
+  fn transition(&mut self, event: Event) {
+    if let Some(transition) = self.state.handle(event, &mut self.world) {
+      self.entered(transition);
     }
   }
 
-  fn check_ball_border_collision(&mut self) {
-    let ball = &mut self.ball;
-    let ball_go = &mut ball.game_object;
-
-    if ball_go.position.x >= WIDTH as f32 - ball_go.size.x {
-        ball.velocity.x = -ball.velocity.x;
-        ball_go.position.x = WIDTH as f32 - ball_go.size.x;
-    } else if ball_go.position[0] <= 0.0 {
-      ball.velocity.x = -ball.velocity.x;
-      ball_go.position.x = 0.0;
+  /// What the window does on top of the world's own setup for a new state.
+  fn entered(&mut self, transition: Transition) {
+    // 关卡加载或重置后，释放上一关用过、现在没人用的纹理
+    if transition.started_level() {
+      self.sprite.release_unused();
+      self.update_title();
     }
-    if ball_go.position.y <= 0.0 {
-      ball.velocity.y = -ball.velocity.y;
-      ball_go.position.y = 0.0;
+    // 一局结束，分数上榜时先输入名字
+    if transition.ended_run() && self.high_scores.qualifies(self.world.scoring.score()) {
+      self.name_entry = Some(String::new());
     }
   }
 
-  fn check_game_over(&mut self) {
-    if self.ball.game_object.position.y > HEIGHT as f32 {
-      self.reset();
-    }
-  }
-
-  fn reset(&mut self) {
-    let (player, ball, bricks) = Self::gen_level();
-    self.player = player;
-    self.ball = ball;
-    self.bricks = bricks;
-  }
-
-  fn update(&mut self, dt: f32) {
-    self.check_ball_border_collision();
-    self.check_ball_player_collision();
-    self.check_ball_brick_collision();
+  pub fn run(&mut self) {
+    self.update_title();
+    let mut last_time = 0.0;
+    while !self.window.win.should_close() {
+      self.process_events();
 
-    let player = &mut self.player;
-    let ball = &mut self.ball;
-    let player_go = &mut player.game_object;
-    let ball_go = &mut ball.game_object;
+      self.sprite.clear(vec3(0.2, 0.3, 0.3));
 
-    if self.keys.contains(&Key::A) {
-      if player_go.position.x > 0.0 {
-        player_go.position.x -= player.velocity * dt;
+      let curr_time = self.window.glfw.get_time() as f32;
+      let steps = self.timestep.advance(curr_time - last_time);
+      for _ in 0..steps {
+        self.update(self.timestep.step());
       }
-    }
+      self.render(self.timestep.alpha());
+      self.sprite.present();
+      last_time = curr_time;
 
-    if self.keys.contains(&Key::D) {
-      if player_go.position.x < WIDTH as f32 - player_go.size.x {
-        player_go.position.x += player.velocity * dt;
-      } 
+      // glfw: swap buffers and poll IO events (keys pressed/released, mouse moved etc.)
+      // -------------------------------------------------------------------------------
+      self.window.win.swap_buffers();
+      self.window.glfw.poll_events();
     }
+  }
+}
 
-    if self.keys.contains(&Key::Space) {
-      ball.is_stuck = false;
-    }
-      
-    if !ball.is_stuck {
-      ball_go.position += ball.velocity * dt;
-    } else {
-      ball_go.position.x = player_go.position.x + player_go.size.x / 2.0 - ball_go.size.x / 2.0;
-      ball_go.position.y = player_go.position.y - ball_go.size.y;
-    }
+impl Game {
+  fn update_title(&mut self) {
+    let campaign = &self.world.campaign;
+    let title = format!("Breakout - level {}/{}", campaign.current() + 1, campaign.len());
+    let title = match campaign.seed() {
+      Some(seed) => format!("{} - seed {}", title, seed),
+      None => title,
+    };
+    self.window.win.set_title(&title);
+  }
 
-    self.draw(&self.bg.game_object);
-    for brick in &self.bricks {
-      if !brick.is_destroyed {
-        self.draw(&brick.game_object);
-      }
+  /// Advances the game by one fixed step of `dt` seconds.
+  fn update(&mut self, dt: f32) {
+    let input = Input {
+      left: self.keys.contains(&Key::A),
+      right: self.keys.contains(&Key::D),
+      launch: self.keys.contains(&Key::Space),
+    };
+    if let Some(transition) = self.state.update(&mut self.world, dt, input) {
+      self.entered(transition);
     }
-    self.draw(&self.player.game_object);
-    self.draw(&self.ball.game_object);
+  }
 
-    self.check_game_over();
+  /// Draws the current state; `alpha` blends moving objects between the last two steps.
+  fn render(&mut self, alpha: f32) {
+    let screen = Screen {
+      state: self.state,
+      world: &self.world,
+      high_scores: &self.high_scores,
+      name_entry: self.name_entry.as_ref().map(String::as_str),
+    };
+    screen.draw(&mut self.sprite, alpha);
   }
-}
\ No newline at end of file
+}
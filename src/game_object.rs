@@ -1,30 +1,51 @@
-use cgmath::{ Vector2, Vector3, vec2, vec3 };
-use crate::texture::Texture;
+use std::sync::atomic::{AtomicU32, Ordering};
+use cgmath::{ Vector2, Vector3, InnerSpace, vec2, vec3 };
+use crate::power_up::PowerUpKind;
 use crate::{WIDTH, HEIGHT};
 
 pub struct GameObject {
   pub position: Vector2<f32>,
+  // 上一个模拟步的位置，渲染时在两者之间插值
+  pub previous_position: Vector2<f32>,
   pub size: Vector2<f32>,
   pub color: Vector3<f32>,
-  pub texture: Texture
+  // 贴图的资源 key，见 assets，由渲染端加载
+  pub texture: &'static str,
 }
 
 impl GameObject {
   pub fn new(
-    src: &str, 
+    src: &'static str, 
     position: Vector2<f32>, 
     size: Vector2<f32>, 
     color: Vector3<f32>, 
   ) -> GameObject {
     GameObject {
       position,
+      previous_position: position,
       size,
       color,
-      texture: Texture::new(src)
+      texture: src
     }
   }
+
+  /// Position blended between the previous and the current simulation step.
+  pub fn interpolated_position(&self, alpha: f32) -> Vector2<f32> {
+    self.previous_position.lerp(self.position, alpha)
+  }
 }
 
+/// Every texture a game object can use, so renderers can load them up front.
+pub const TEXTURES: [&str; 5] = [
+  "res/bg.jpg",
+  "res/face.png",
+  "res/block.png",
+  "res/block_solid.png",
+  "res/paddle.png",
+];
+
+const INITIAL_BALL_VELOCITY: Vector2<f32> = Vector2 { x: 150.0, y: -500.0 };
+
 pub struct Ball {
   pub game_object: GameObject,
   pub velocity: Vector2<f32>,
@@ -37,16 +58,19 @@ impl Ball {
     Ball {
       is_stuck: true,
       radius: 12.5,
-      velocity: vec2(150.0, -500.0),
-      game_object: GameObject::new("src/res/face.png", position, vec2(25.0, 25.0), vec3(1.0, 1.0, 1.0))
+      velocity: INITIAL_BALL_VELOCITY,
+      game_object: GameObject::new("res/face.png", position, vec2(25.0, 25.0), vec3(1.0, 1.0, 1.0))
     }
   }
 }
 
-static mut NEXT_BRICK_ID: u32 = 0;
+// 原子计数，多个线程同时创建砖块时编号也不会重复
+static NEXT_BRICK_ID: AtomicU32 = AtomicU32::new(1);
 
 pub struct Brick {
   pub game_object: GameObject,
+  // 关卡文件中的砖块编号
+  pub kind: u8,
   pub is_solid: bool,
   pub is_destroyed: bool,
   id: u32,
@@ -59,18 +83,21 @@ impl PartialEq for Brick {
 }
 
 impl Brick {
-  pub fn new(position: Vector2<f32>, size: Vector2<f32>, color: Vector3<f32>, is_solid: bool) -> Brick {
-    let src = if is_solid { "src/res/block_solid.png" } else { "src/res/block.png" };
-    unsafe {
-      NEXT_BRICK_ID += 1;
-        Brick {
-        id: NEXT_BRICK_ID,
-        is_solid,
-        is_destroyed: false,
-        game_object: GameObject::new(src, position, size, color)
-      }
+  pub fn new(kind: u8, position: Vector2<f32>, size: Vector2<f32>, color: Vector3<f32>, is_solid: bool) -> Brick {
+    let src = if is_solid { "res/block_solid.png" } else { "res/block.png" };
+    Brick {
+      id: NEXT_BRICK_ID.fetch_add(1, Ordering::Relaxed),
+      kind,
+      is_solid,
+      is_destroyed: false,
+      game_object: GameObject::new(src, position, size, color)
     }
   }
+
+  /// Unique for every brick created, so contacts can name the brick they involve.
+  pub fn id(&self) -> u32 {
+    self.id
+  }
 }
 
 pub struct Bg {
@@ -80,7 +107,7 @@ pub struct Bg {
 impl Bg {
   pub fn new() -> Bg {
     Bg {
-      game_object: GameObject::new("src/res/bg.jpg", vec2(0.0, 0.0), vec2(WIDTH as f32, HEIGHT as f32), vec3(1.0, 1.0, 1.0))
+      game_object: GameObject::new("res/bg.jpg", vec2(0.0, 0.0), vec2(WIDTH as f32, HEIGHT as f32), vec3(1.0, 1.0, 1.0))
     }
   }
 }
@@ -93,10 +120,24 @@ pub struct Paddle {
 impl Paddle {
   pub fn new(position: Vector2<f32>) -> Paddle {
     Paddle {
-      game_object: GameObject::new("src/res/paddle.png", position, vec2(100.0, 20.0), vec3(1.0, 1.0, 1.0)),
+      game_object: GameObject::new("res/paddle.png", position, vec2(100.0, 20.0), vec3(1.0, 1.0, 1.0)),
       velocity: 500.0
     }
   }
 }
 
+pub struct PowerUp {
+  pub game_object: GameObject,
+  pub kind: PowerUpKind,
+  pub velocity: Vector2<f32>,
+}
 
+impl PowerUp {
+  pub fn new(kind: PowerUpKind, position: Vector2<f32>) -> PowerUp {
+    PowerUp {
+      game_object: GameObject::new("res/block.png", position, vec2(60.0, 20.0), kind.color()),
+      kind,
+      velocity: vec2(0.0, 150.0),
+    }
+  }
+}
@@ -0,0 +1,153 @@
+use std::cmp::Reverse;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const MAX_ENTRIES: usize = 10;
+pub const MAX_NAME_LEN: usize = 10;
+
+const HEADER: &str = "# breakout high scores v1";
+
+pub struct HighScore {
+  pub name: String,
+  pub score: u32,
+  // 到达的关卡，从 1 开始
+  pub level: usize,
+  pub seed: Option<u64>,
+  // YYYY-MM-DD
+  pub date: String,
+}
+
+/// The best `MAX_ENTRIES` scores, highest first, kept in a tab separated text file:
+/// `score level seed date name`, with `-` for runs that had no seed.
+pub struct HighScores {
+  entries: Vec<HighScore>,
+  path: Option<PathBuf>,
+}
+
+impl HighScores {
+  /// `<data dir>/breakout/highscores.txt`, if the platform has a data directory.
+  pub fn default_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("breakout").join("highscores.txt"))
+  }
+
+  /// Reads the table from `path`. A missing file gives an empty table and
+  /// malformed lines are skipped, so a damaged file never stops the game.
+  /// With no path the table only lives in memory.
+  pub fn load(path: Option<PathBuf>) -> HighScores {
+    let mut entries = vec![];
+    if let Some(path) = &path {
+      match fs::read_to_string(path) {
+        Ok(text) => {
+          for (idx, line) in text.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+              continue;
+            }
+            match parse_entry(line) {
+              Some(entry) => entries.push(entry),
+              None => eprintln!("{}:{}: ignoring malformed high score", path.display(), idx + 1),
+            }
+          }
+        },
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => {},
+        Err(e) => eprintln!("Failed to read high scores {}: {}", path.display(), e),
+      }
+    }
+    entries.sort_by_key(|e| Reverse(e.score));
+    entries.truncate(MAX_ENTRIES);
+    HighScores { entries, path }
+  }
+
+  pub fn entries(&self) -> &[HighScore] {
+    &self.entries
+  }
+
+  /// Whether `score` would make it onto the table.
+  pub fn qualifies(&self, score: u32) -> bool {
+    score > 0 && (self.entries.len() < MAX_ENTRIES || self.entries.iter().any(|e| score > e.score))
+  }
+
+  /// Inserts `entry` behind any equal scores and returns its 0-based rank,
+  /// or `None` if it didn't make the table.
+  pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+    let rank = self.entries.iter().position(|e| entry.score > e.score).unwrap_or(self.entries.len());
+    if rank >= MAX_ENTRIES {
+      return None;
+    }
+    self.entries.insert(rank, entry);
+    self.entries.truncate(MAX_ENTRIES);
+    Some(rank)
+  }
+
+  pub fn save(&self) -> io::Result<()> {
+    let path = match &self.path {
+      Some(path) => path,
+      None => return Ok(()),
+    };
+    if let Some(dir) = path.parent() {
+      fs::create_dir_all(dir)?;
+    }
+    let mut text = String::from(HEADER);
+    text.push('\n');
+    for e in &self.entries {
+      let seed = e.seed.map_or(String::from("-"), |seed| seed.to_string());
+      text.push_str(&format!("{}\t{}\t{}\t{}\t{}\n", e.score, e.level, seed, e.date, e.name));
+    }
+    // 先写临时文件再替换，避免写到一半时留下损坏的文件
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, text)?;
+    fs::rename(&tmp, path)
+  }
+}
+
+fn parse_entry(line: &str) -> Option<HighScore> {
+  let mut fields = line.splitn(5, '\t');
+  let score = fields.next()?.parse().ok()?;
+  let level = fields.next()?.parse().ok()?;
+  let seed = match fields.next()? {
+    "-" => None,
+    seed => Some(seed.parse().ok()?),
+  };
+  let date = fields.next()?.to_string();
+  let name: String = fields.next()?.chars().take(MAX_NAME_LEN).collect();
+  Some(HighScore { name, score, level, seed, date })
+}
+
+/// Today's UTC date as YYYY-MM-DD.
+pub fn today() -> String {
+  let secs = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+  let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+  format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// reference: http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+  let z = days + 719_468;
+  let era = z.div_euclid(146_097);
+  let doe = z.rem_euclid(146_097);
+  let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+  let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+  let mp = (5 * doy + 2) / 153;
+  let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+  let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+  let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+  (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn days_since_the_epoch_become_dates() {
+    assert_eq!(civil_from_days(0), (1970, 1, 1));
+    assert_eq!(civil_from_days(-1), (1969, 12, 31));
+    assert_eq!(civil_from_days(59), (1970, 3, 1));
+    // 2000 年是闰年，1900 年不是
+    assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+    assert_eq!(civil_from_days(11_017), (2000, 3, 1));
+    assert_eq!(civil_from_days(-25_508), (1900, 3, 1));
+    assert_eq!(civil_from_days(19_723), (2024, 1, 1));
+  }
+}
@@ -0,0 +1,203 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use cgmath::{vec2, vec3, Vector2};
+use rand::prelude::*;
+use rand::distributions::WeightedIndex;
+use crate::game_object::Brick;
+
+/// Tile code of the breakable brick that splits the ball in two.
+pub const MULTI_BALL_BRICK: u8 = 6;
+
+/// A brick layout, one tile code per cell:
+/// 0 empty, 1 solid, 2-5 breakable bricks of different colors,
+/// 6 a breakable brick that splits the ball in two.
+pub struct Level {
+  width: usize,
+  height: usize,
+  tiles: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub enum LevelError {
+  Io(String, io::Error),
+  Empty(String),
+  InvalidTile { src: String, line: usize, column: usize, token: String },
+  RowLength { src: String, line: usize, expected: usize, found: usize },
+}
+
+impl fmt::Display for LevelError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      LevelError::Io(src, e) => write!(f, "{}: {}", src, e),
+      LevelError::Empty(src) => write!(f, "{}: level contains no rows", src),
+      LevelError::InvalidTile { src, line, column, token } =>
+        write!(f, "{}:{}:{}: invalid tile `{}`, expected a code from 0 to 6", src, line, column, token),
+      LevelError::RowLength { src, line, expected, found } =>
+        write!(f, "{}:{}: row has {} tiles, expected {}", src, line, found, expected),
+    }
+  }
+}
+
+impl Level {
+  pub fn load(path: &str) -> Result<Level, LevelError> {
+    let text = fs::read_to_string(path).map_err(|e| LevelError::Io(path.to_string(), e))?;
+    Self::parse(path, &text)
+  }
+
+  /// Parses a tile grid: one row per line, tile codes separated by whitespace.
+  /// Blank lines are skipped.
+  pub fn parse(label: &str, text: &str) -> Result<Level, LevelError> {
+    let mut width = 0;
+    let mut height = 0;
+    let mut tiles = vec![];
+
+    for (line_idx, line) in text.lines().enumerate() {
+      let line_no = line_idx + 1;
+      let mut row_width = 0;
+      for (column, token) in tokens(line) {
+        let code = match token.parse::<u8>() {
+          Ok(code) if code <= MULTI_BALL_BRICK => code,
+          _ => return Err(LevelError::InvalidTile {
+            src: label.to_string(),
+            line: line_no,
+            column,
+            token: token.to_string(),
+          }),
+        };
+        tiles.push(code);
+        row_width += 1;
+      }
+      if row_width == 0 {
+        continue;
+      }
+      if height == 0 {
+        width = row_width;
+      } else if row_width != width {
+        return Err(LevelError::RowLength { src: label.to_string(), line: line_no, expected: width, found: row_width });
+      }
+      height += 1;
+    }
+
+    if height == 0 {
+      return Err(LevelError::Empty(label.to_string()));
+    }
+
+    Ok(Level { width, height, tiles })
+  }
+
+  /// Random layout drawn from `rng`. With a seeded `Pcg32` the same seed gives the same
+  /// layout on every platform.
+  pub fn random<R: Rng>(width: usize, height: usize, rng: &mut R) -> Level {
+    let choices = [1, 2, 3, 4, 5];
+    let weights = [3, 4, 4, 4, 4];
+    let dist = WeightedIndex::new(&weights).unwrap();
+
+    let tiles = (0..width * height).map(|_| choices[dist.sample(rng)]).collect();
+
+    Level { width, height, tiles }
+  }
+
+  /// Size of one tile when the level is laid out over `lv_width` x `lv_height`.
+  pub fn brick_size(&self, lv_width: f32, lv_height: f32) -> Vector2<f32> {
+    vec2(lv_width / self.width as f32, lv_height / self.height as f32)
+  }
+
+  /// Lays the grid out over a `lv_width` x `lv_height` area at the top of the playfield.
+  pub fn bricks(&self, lv_width: f32, lv_height: f32) -> Vec<Brick> {
+    let size = self.brick_size(lv_width, lv_height);
+    let (unit_width, unit_height) = (size.x, size.y);
+
+    let mut bricks = vec![];
+    for (idx, t) in self.tiles.iter().enumerate() {
+      let row = idx / self.width;
+      let column = idx % self.width;
+      let position = vec2(column as f32 * unit_width, row as f32 * unit_height);
+      if let Some(brick) = Self::brick(*t, position, size) {
+        bricks.push(brick);
+      }
+    }
+    bricks
+  }
+
+  fn brick(tile: u8, position: Vector2<f32>, size: Vector2<f32>) -> Option<Brick> {
+    match tile {
+      1 => Some(Brick::new(tile, position, size, vec3(0.8, 0.8, 0.7), true)),
+      2 => Some(Brick::new(tile, position, size, vec3(0.2, 0.6, 1.0), false)),
+      3 => Some(Brick::new(tile, position, size, vec3(0.0, 0.7, 0.0), false)),
+      4 => Some(Brick::new(tile, position, size, vec3(0.8, 0.8, 0.4), false)),
+      5 => Some(Brick::new(tile, position, size, vec3(1.0, 0.5, 0.0), false)),
+      MULTI_BALL_BRICK => Some(Brick::new(tile, position, size, vec3(1.0, 0.3, 0.7), false)),
+      _ => None
+    }
+  }
+}
+
+/// Splits a line on whitespace, yielding each token with its 1-based column.
+fn tokens(line: &str) -> impl Iterator<Item = (usize, &str)> {
+  line.split_whitespace().map(move |token| {
+    let offset = token.as_ptr() as usize - line.as_ptr() as usize;
+    (line[..offset].chars().count() + 1, token)
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use rand::SeedableRng;
+  use rand_pcg::Pcg32;
+  use super::*;
+
+  #[test]
+  fn seed_determines_the_layout() {
+    let level = Level::random(6, 3, &mut Pcg32::seed_from_u64(42));
+    assert_eq!(level.tiles, vec![
+      5, 4, 1, 5, 2, 4,
+      5, 2, 5, 2, 1, 3,
+      2, 3, 1, 5, 3, 1,
+    ]);
+    assert_eq!(Level::random(6, 3, &mut Pcg32::seed_from_u64(42)).tiles, level.tiles);
+  }
+  #[test]
+  fn parses_rows_of_tile_codes() {
+    let level = Level::parse("test.lvl", "1 2 3\n\n 4 5 6 \n").unwrap();
+    assert_eq!((level.width, level.height), (3, 2));
+    assert_eq!(level.tiles, vec![1, 2, 3, 4, 5, 6]);
+  }
+
+  #[test]
+  fn unknown_tile_is_reported_with_its_position() {
+    for &(text, line, column, bad) in &[("1 2 3\n4 x 5\n", 2, 3, "x"), ("1  7", 1, 4, "7"), ("\n\t-1", 2, 2, "-1")] {
+      let err = Level::parse("test.lvl", text).err().unwrap();
+      match err {
+        LevelError::InvalidTile { ref src, line: l, column: c, ref token } => {
+          assert_eq!((src.as_str(), l, c, token.as_str()), ("test.lvl", line, column, bad));
+        },
+        ref err => panic!("expected an invalid tile, got {:?}", err),
+      }
+      assert_eq!(err.to_string(), format!("test.lvl:{}:{}: invalid tile `{}`, expected a code from 0 to 6", line, column, bad));
+    }
+  }
+
+  #[test]
+  fn ragged_row_is_reported_with_its_line() {
+    // 空行不算一行，但行号照常计数
+    let err = Level::parse("test.lvl", "1 1 1\n\n2 2\n").err().unwrap();
+    match err {
+      LevelError::RowLength { line: 3, expected: 3, found: 2, .. } => {},
+      ref err => panic!("expected a ragged row on line 3, got {:?}", err),
+    }
+    assert_eq!(err.to_string(), "test.lvl:3: row has 2 tiles, expected 3");
+  }
+
+  #[test]
+  fn level_without_rows_is_empty() {
+    for &text in &["", "\n  \n\t\n"] {
+      let err = Level::parse("test.lvl", text).err().unwrap();
+      match err {
+        LevelError::Empty(ref src) => assert_eq!(src, "test.lvl"),
+        ref err => panic!("expected an empty level, got {:?}", err),
+      }
+      assert_eq!(err.to_string(), "test.lvl: level contains no rows");
+    }
+  }
+}
@@ -0,0 +1,26 @@
+pub static WIDTH: u32 = 800;
+pub static HEIGHT: u32 = 600;
+
+extern crate cgmath;
+pub mod game_object;
+pub mod level;
+pub mod campaign;
+pub mod options;
+pub mod state;
+pub mod lives;
+pub mod score;
+pub mod highscore;
+pub mod power_up;
+pub mod timestep;
+pub mod collision;
+pub mod broadphase;
+pub mod world;
+pub mod renderer;
+pub mod text;
+pub mod screen;
+pub mod software_renderer;
+pub mod texture_data;
+pub mod resources;
+pub mod assets;
+pub mod texture_options;
+pub mod atlas;
@@ -0,0 +1,42 @@
+/// Lives left in the current run, plus extra lives earned from score.
+pub struct Lives {
+  remaining: u32,
+  // 每获得多少分奖励一条命，0 表示不奖励
+  extra_life_every: u32,
+  next_extra_life_at: u32,
+}
+
+impl Lives {
+  pub fn new(count: u32, extra_life_every: u32) -> Lives {
+    Lives {
+      remaining: count,
+      extra_life_every,
+      next_extra_life_at: extra_life_every,
+    }
+  }
+
+  pub fn remaining(&self) -> u32 {
+    self.remaining
+  }
+
+  /// Takes a life away. Returns `false` once none are left.
+  pub fn lose(&mut self) -> bool {
+    self.remaining = self.remaining.saturating_sub(1);
+    self.remaining > 0
+  }
+
+  /// Awards a life for every threshold `score` has passed since the last call.
+  /// Returns the number of lives gained.
+  pub fn award_for_score(&mut self, score: u32) -> u32 {
+    if self.extra_life_every == 0 {
+      return 0;
+    }
+    let mut gained = 0;
+    while score >= self.next_extra_life_at {
+      self.next_extra_life_at += self.extra_life_every;
+      gained += 1;
+    }
+    self.remaining += gained;
+    gained
+  }
+}
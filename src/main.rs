@@ -1,6 +1,3 @@
-static WIDTH: u32 = 800;
-static HEIGHT: u32 = 600;
-
 extern crate gl;
 extern crate cgmath;
 mod macros;
@@ -9,9 +6,30 @@ mod game;
 mod shader;
 mod sprite;
 mod texture;
-mod game_object;
+
+use std::{env, process};
+use std::path::PathBuf;
+use breakout::assets::Assets;
+use breakout::campaign::Campaign;
+use breakout::options::{self, Options};
 
 fn main() {
-    let mut game = game::Game::new();
+    let options = Options::parse(env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("{}\n{}", e, options::USAGE);
+        process::exit(2);
+    });
+    // 按顺序游玩的关卡文件，不指定时随机生成关卡
+    let campaign = Campaign::from_options(&options).unwrap_or_else(|e| {
+        eprintln!("Failed to load level: {}", e);
+        process::exit(1);
+    });
+    let assets = Assets::new(options.assets.map(PathBuf::from)).unwrap_or_else(|e| {
+        eprintln!("Failed to load texture manifest: {}", e);
+        process::exit(1);
+    });
+    let mut game = game::Game::new(campaign, options.lives, &assets).unwrap_or_else(|e| {
+        eprintln!("Failed to start: {}", e);
+        process::exit(1);
+    });
     game.run();
 }
@@ -0,0 +1,44 @@
+/// Command line options.
+pub struct Options {
+  pub seed: Option<u64>,
+  pub lives: u32,
+  pub levels: Vec<String>,
+  // 覆盖内置资源的目录，结构同 src/
+  pub assets: Option<String>,
+}
+
+pub const USAGE: &str = "Usage: breakout [--seed <n>] [--lives <n>] [--assets <dir>] [level files...]";
+
+impl Options {
+  pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+    let mut options = Options {
+      seed: None,
+      lives: 3,
+      levels: vec![],
+      assets: None,
+    };
+    while let Some(arg) = args.next() {
+      match &arg[..] {
+        "--seed" => {
+          let value = args.next().ok_or("--seed needs a value")?;
+          let seed = value.parse().map_err(|_| format!("invalid seed `{}`", value))?;
+          options.seed = Some(seed);
+        },
+        "--lives" => {
+          let value = args.next().ok_or("--lives needs a value")?;
+          options.lives = match value.parse() {
+            Ok(lives) if lives > 0 => lives,
+            _ => return Err(format!("invalid number of lives `{}`", value)),
+          };
+        },
+        "--assets" => {
+          let value = args.next().ok_or("--assets needs a directory")?;
+          options.assets = Some(value);
+        },
+        _ if arg.starts_with("--") => return Err(format!("unknown option `{}`", arg)),
+        _ => options.levels.push(arg),
+      }
+    }
+    Ok(options)
+  }
+}
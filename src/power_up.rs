@@ -0,0 +1,133 @@
+use cgmath::{vec3, Vector3};
+use rand::Rng;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PowerUpKind {
+  Speed,
+  Sticky,
+  PassThrough,
+  PadSizeIncrease,
+  MultiBall,
+  Confuse,
+  Chaos,
+}
+
+/// What picking up a power-up that is already active does to its timer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Stacking {
+  /// Restart the timer at the full duration.
+  Refresh,
+  /// Add the full duration to what is left.
+  Extend,
+}
+
+impl PowerUpKind {
+  pub fn color(self) -> Vector3<f32> {
+    match self {
+      PowerUpKind::Speed => vec3(0.5, 0.5, 1.0),
+      PowerUpKind::Sticky => vec3(1.0, 0.5, 1.0),
+      PowerUpKind::PassThrough => vec3(0.5, 1.0, 0.5),
+      PowerUpKind::PadSizeIncrease => vec3(1.0, 0.6, 0.4),
+      PowerUpKind::MultiBall => vec3(1.0, 1.0, 0.4),
+      PowerUpKind::Confuse => vec3(1.0, 0.3, 0.3),
+      PowerUpKind::Chaos => vec3(0.9, 0.25, 0.25),
+    }
+  }
+
+  /// Seconds the effect lasts, 0 for one-off effects.
+  pub fn duration(self) -> f32 {
+    match self {
+      PowerUpKind::Speed => 10.0,
+      PowerUpKind::Sticky => 20.0,
+      PowerUpKind::PassThrough => 10.0,
+      PowerUpKind::PadSizeIncrease => 15.0,
+      PowerUpKind::MultiBall => 0.0,
+      PowerUpKind::Confuse => 15.0,
+      PowerUpKind::Chaos => 15.0,
+    }
+  }
+
+  pub fn stacking(self) -> Stacking {
+    match self {
+      PowerUpKind::Sticky | PowerUpKind::PassThrough => Stacking::Extend,
+      PowerUpKind::Speed | PowerUpKind::PadSizeIncrease | PowerUpKind::MultiBall | PowerUpKind::Confuse | PowerUpKind::Chaos => Stacking::Refresh,
+    }
+  }
+}
+
+/// Chance of each power-up dropping when a brick of tile code `brick_kind` is destroyed.
+/// Bricks higher up the board are worth more and drop good power-ups more often.
+pub fn drop_chances(brick_kind: u8) -> &'static [(PowerUpKind, f32)] {
+  use PowerUpKind::*;
+  match brick_kind {
+    2 => &[(Speed, 0.01), (Sticky, 0.01), (PassThrough, 0.01), (PadSizeIncrease, 0.01), (MultiBall, 0.01), (Confuse, 0.06), (Chaos, 0.06)],
+    3 => &[(Speed, 0.02), (Sticky, 0.02), (PassThrough, 0.01), (PadSizeIncrease, 0.02), (MultiBall, 0.01), (Confuse, 0.05), (Chaos, 0.05)],
+    4 => &[(Speed, 0.02), (Sticky, 0.03), (PassThrough, 0.02), (PadSizeIncrease, 0.03), (MultiBall, 0.02), (Confuse, 0.04), (Chaos, 0.04)],
+    5 => &[(Speed, 0.03), (Sticky, 0.03), (PassThrough, 0.03), (PadSizeIncrease, 0.03), (MultiBall, 0.02), (Confuse, 0.03), (Chaos, 0.03)],
+    _ => &[],
+  }
+}
+
+/// Picks the power-up, if any, dropped by a destroyed brick.
+pub fn roll_drop<R: Rng>(brick_kind: u8, rng: &mut R) -> Option<PowerUpKind> {
+  let mut roll: f32 = rng.gen();
+  for &(kind, chance) in drop_chances(brick_kind) {
+    if roll < chance {
+      return Some(kind);
+    }
+    roll -= chance;
+  }
+  None
+}
+
+/// Timers of the effects currently in play.
+#[derive(Default)]
+pub struct ActivePowerUps {
+  timers: Vec<(PowerUpKind, f32)>,
+}
+
+impl ActivePowerUps {
+  pub fn new() -> ActivePowerUps {
+    Self::default()
+  }
+
+  pub fn is_active(&self, kind: PowerUpKind) -> bool {
+    self.timers.iter().any(|&(k, _)| k == kind)
+  }
+
+  /// Starts or stacks `kind`'s timer. Returns `true` if the effect wasn't active before,
+  /// i.e. it still has to be applied.
+  pub fn activate(&mut self, kind: PowerUpKind) -> bool {
+    match self.timers.iter_mut().find(|(k, _)| *k == kind) {
+      Some((_, remaining)) => {
+        *remaining = match kind.stacking() {
+          Stacking::Refresh => remaining.max(kind.duration()),
+          Stacking::Extend => *remaining + kind.duration(),
+        };
+        false
+      },
+      None => {
+        self.timers.push((kind, kind.duration()));
+        true
+      },
+    }
+  }
+
+  /// Counts the timers down and returns the effects that ran out.
+  pub fn tick(&mut self, dt: f32) -> Vec<PowerUpKind> {
+    let mut expired = vec![];
+    for (kind, remaining) in self.timers.iter_mut() {
+      *remaining -= dt;
+      if *remaining <= 0.0 {
+        expired.push(*kind);
+      }
+    }
+    self.timers.retain(|&(_, remaining)| remaining > 0.0);
+    expired
+  }
+
+  /// Removes every effect, returning the ones that were active.
+  pub fn clear(&mut self) -> Vec<PowerUpKind> {
+    self.timers.drain(..).map(|(kind, _)| kind).collect()
+  }
+}
@@ -0,0 +1,88 @@
+use cgmath::{vec3, ortho, Deg, Matrix4, Vector2, Vector3};
+
+/// Where frames go. Textures are named by key: the paths in `game_object::TEXTURES`
+/// plus `text::TEXTURE`; each backend loads them however it likes.
+pub trait Renderer {
+  /// Fills the whole frame with `color`.
+  fn clear(&mut self, color: Vector3<f32>);
+  /// Draws `texture` stretched over `position`..`position + size`, rotated by `rotate`
+  /// degrees around its centre and multiplied by `color`.
+  fn draw_sprite(&mut self, texture: &str, position: Vector2<f32>, size: Vector2<f32>, rotate: f32, color: Vector3<f32>);
+  /// Ends the frame.
+  fn present(&mut self);
+}
+
+/// Part of a texture to draw, in texture coordinates (0..1, `v` down from the top row).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UvRect {
+  pub min: Vector2<f32>,
+  pub max: Vector2<f32>,
+}
+
+impl UvRect {
+  /// The whole texture.
+  pub const FULL: UvRect = UvRect { min: Vector2 { x: 0.0, y: 0.0 }, max: Vector2 { x: 1.0, y: 1.0 } };
+
+  /// The point `t` of the way across, `t` going from 0,0 at `min` to 1,1 at `max`.
+  pub fn lerp(&self, t: Vector2<f32>) -> Vector2<f32> {
+    Vector2::new(self.min.x + (self.max.x - self.min.x) * t.x, self.min.y + (self.max.y - self.min.y) * t.y)
+  }
+}
+
+/// Model matrix placing the unit quad at `position` with `size`, rotated by `rotate`
+/// degrees around its centre.
+pub fn model_matrix(position: Vector2<f32>, size: Vector2<f32>, rotate: f32) -> Matrix4<f32> {
+  // 缩放
+  let mut model = Matrix4::from_nonuniform_scale(size.x, size.y, 1.0);
+  // 将中心移动到原点
+  model = Matrix4::from_translation(vec3(-0.5 * size.x, -0.5 * size.y, 1.0)) * model;
+  // 绕原点旋转
+  model = Matrix4::from_angle_z(Deg(rotate)) * model;
+  // 移动
+  Matrix4::from_translation(vec3(position.x + 0.5 * size.x, position.y + 0.5 * size.y, 0.0)) * model
+}
+
+/// Projection mapping window pixels, origin top left, to clip space.
+pub fn projection_matrix(width: u32, height: u32) -> Matrix4<f32> {
+  ortho(0.0, width as f32, height as f32, 0.0, -1.0, 1.0)
+}
+
+/// One call made on a `RecordingRenderer`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DrawCall {
+  Clear(Vector3<f32>),
+  Sprite {
+    texture: String,
+    position: Vector2<f32>,
+    size: Vector2<f32>,
+    rotate: f32,
+    color: Vector3<f32>,
+  },
+  Present,
+}
+
+/// Draws nothing, just records every call in order.
+#[derive(Default)]
+pub struct RecordingRenderer {
+  pub calls: Vec<DrawCall>,
+}
+
+impl RecordingRenderer {
+  pub fn new() -> RecordingRenderer {
+    Self::default()
+  }
+}
+
+impl Renderer for RecordingRenderer {
+  fn clear(&mut self, color: Vector3<f32>) {
+    self.calls.push(DrawCall::Clear(color));
+  }
+
+  fn draw_sprite(&mut self, texture: &str, position: Vector2<f32>, size: Vector2<f32>, rotate: f32, color: Vector3<f32>) {
+    self.calls.push(DrawCall::Sprite { texture: String::from(texture), position, size, rotate, color });
+  }
+
+  fn present(&mut self) {
+    self.calls.push(DrawCall::Present);
+  }
+}
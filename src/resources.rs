@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Loads each resource once per key and hands out shared handles to it.
+/// A resource lives as long as any handle (or the manager) holds it;
+/// `release_unused` lets go of the ones nobody else holds any more.
+pub struct ResourceManager<T> {
+  load: Box<dyn Fn(&str) -> T>,
+  resources: HashMap<String, Rc<T>>,
+  // 每个 key 从磁盘加载过的次数，释放后重新加载会再加一
+  loads: HashMap<String, usize>,
+}
+
+impl<T> ResourceManager<T> {
+  /// An empty manager that loads missing resources with `load`.
+  pub fn new<F: Fn(&str) -> T + 'static>(load: F) -> ResourceManager<T> {
+    ResourceManager {
+      load: Box::new(load),
+      resources: HashMap::new(),
+      loads: HashMap::new(),
+    }
+  }
+
+  /// The resource for `key`, loading it first if it isn't loaded yet.
+  pub fn get(&mut self, key: &str) -> Rc<T> {
+    if let Some(resource) = self.resources.get(key) {
+      return Rc::clone(resource);
+    }
+    let resource = Rc::new((self.load)(key));
+    *self.loads.entry(String::from(key)).or_insert(0) += 1;
+    self.resources.insert(String::from(key), Rc::clone(&resource));
+    resource
+  }
+
+  /// Stores a resource made in code under `key`, replacing any loaded one.
+  /// This doesn't count as a load.
+  pub fn insert(&mut self, key: &str, resource: T) -> Rc<T> {
+    let resource = Rc::new(resource);
+    self.resources.insert(String::from(key), Rc::clone(&resource));
+    resource
+  }
+
+  /// Drops every resource that only the manager still holds and returns how many went.
+  pub fn release_unused(&mut self) -> usize {
+    let before = self.resources.len();
+    self.resources.retain(|_, resource| Rc::strong_count(resource) > 1);
+    before - self.resources.len()
+  }
+
+  /// How many times `key` has been loaded.
+  pub fn load_count(&self, key: &str) -> usize {
+    self.loads.get(key).cloned().unwrap_or(0)
+  }
+
+  /// How many loads happened in total, over all keys.
+  pub fn total_loads(&self) -> usize {
+    self.loads.values().sum()
+  }
+
+  /// Number of resources currently held.
+  pub fn len(&self) -> usize {
+    self.resources.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.resources.is_empty()
+  }
+}
@@ -0,0 +1,147 @@
+/// Something that changed the score, as reported by the collision code.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScoreEvent {
+  BrickDestroyed { kind: u8, points: u32, multiplier: u32 },
+  LevelBonus { points: u32, seconds: f32 },
+}
+
+impl ScoreEvent {
+  pub fn total(&self) -> u32 {
+    match *self {
+      ScoreEvent::BrickDestroyed { points, multiplier, .. } => points * multiplier,
+      ScoreEvent::LevelBonus { points, .. } => points,
+    }
+  }
+}
+
+const MAX_MULTIPLIER: u32 = 8;
+// 在此时间内清空关卡可获得奖励，每提前一秒奖励 BONUS_PER_SECOND 分
+const PAR_SECONDS: f32 = 120.0;
+const BONUS_PER_SECOND: f32 = 10.0;
+
+/// Base points for a brick tile code; solid bricks can't be destroyed and give nothing.
+pub fn brick_points(kind: u8) -> u32 {
+  match kind {
+    2 => 10,
+    3 => 20,
+    4 => 30,
+    5 => 40,
+    6 => 50,
+    _ => 0,
+  }
+}
+
+/// Running score. The multiplier grows with every brick destroyed since the
+/// ball last touched the paddle.
+#[derive(Default)]
+pub struct Scoring {
+  score: u32,
+  combo: u32,
+  level_time: f32,
+}
+
+impl Scoring {
+  pub fn new() -> Scoring {
+    Self::default()
+  }
+
+  pub fn score(&self) -> u32 {
+    self.score
+  }
+
+  /// Multiplier the next destroyed brick will get.
+  pub fn multiplier(&self) -> u32 {
+    (self.combo + 1).min(MAX_MULTIPLIER)
+  }
+
+  pub fn start_level(&mut self) {
+    self.combo = 0;
+    self.level_time = 0.0;
+  }
+
+  pub fn tick(&mut self, dt: f32) {
+    self.level_time += dt;
+  }
+
+  pub fn brick_destroyed(&mut self, kind: u8) -> ScoreEvent {
+    let event = ScoreEvent::BrickDestroyed {
+      kind,
+      points: brick_points(kind),
+      multiplier: self.multiplier(),
+    };
+    self.combo += 1;
+    self.apply(event)
+  }
+
+  pub fn paddle_hit(&mut self) {
+    self.combo = 0;
+  }
+
+  /// Time bonus for clearing the level under par, if any.
+  pub fn level_cleared(&mut self) -> Option<ScoreEvent> {
+    let seconds = self.level_time;
+    if seconds >= PAR_SECONDS {
+      return None;
+    }
+    let points = ((PAR_SECONDS - seconds) * BONUS_PER_SECOND) as u32;
+    Some(self.apply(ScoreEvent::LevelBonus { points, seconds }))
+  }
+
+  fn apply(&mut self, event: ScoreEvent) -> ScoreEvent {
+    self.score += event.total();
+    event
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn each_brick_type_has_its_value() {
+    let values: Vec<u32> = (0..=6).map(brick_points).collect();
+    assert_eq!(values, vec![0, 0, 10, 20, 30, 40, 50]);
+
+    for kind in 2..=6 {
+      let mut scoring = Scoring::new();
+      let event = scoring.brick_destroyed(kind);
+      assert_eq!(event, ScoreEvent::BrickDestroyed { kind, points: brick_points(kind), multiplier: 1 });
+      assert_eq!(scoring.score(), brick_points(kind));
+    }
+  }
+
+  #[test]
+  fn combo_grows_with_every_brick_up_to_the_cap() {
+    let mut scoring = Scoring::new();
+    let multipliers: Vec<u32> = (0..10).map(|_| match scoring.brick_destroyed(2) {
+      ScoreEvent::BrickDestroyed { multiplier, .. } => multiplier,
+      event => panic!("unexpected {:?}", event),
+    }).collect();
+    assert_eq!(multipliers, vec![1, 2, 3, 4, 5, 6, 7, 8, 8, 8]);
+    assert_eq!(scoring.score(), 10 * multipliers.iter().sum::<u32>());
+  }
+
+  #[test]
+  fn paddle_hit_resets_the_combo() {
+    let mut scoring = Scoring::new();
+    scoring.brick_destroyed(3);
+    scoring.brick_destroyed(3);
+    assert_eq!(scoring.multiplier(), 3);
+    scoring.paddle_hit();
+    assert_eq!(scoring.multiplier(), 1);
+    assert_eq!(scoring.brick_destroyed(3).total(), 20);
+  }
+
+  #[test]
+  fn clearing_fast_earns_a_bonus() {
+    let mut scoring = Scoring::new();
+    scoring.tick(20.0);
+    assert_eq!(scoring.level_cleared(), Some(ScoreEvent::LevelBonus { points: 1000, seconds: 20.0 }));
+    assert_eq!(scoring.score(), 1000);
+
+    scoring.start_level();
+    scoring.tick(200.0);
+    assert_eq!(scoring.level_cleared(), None);
+    assert_eq!(scoring.score(), 1000);
+  }
+}
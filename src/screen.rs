@@ -0,0 +1,153 @@
+use cgmath::{vec2, vec3};
+use crate::WIDTH;
+use crate::game_object::GameObject;
+use crate::highscore::HighScores;
+use crate::power_up::PowerUpKind;
+use crate::renderer::Renderer;
+use crate::state::State;
+use crate::text;
+use crate::world::World;
+
+/// Everything that goes into a frame: the world plus the screen drawn over it.
+/// Draws through any `Renderer`, so frames can be checked without a window.
+pub struct Screen<'a> {
+  pub state: State,
+  pub world: &'a World,
+  pub high_scores: &'a HighScores,
+  // 正在输入的上榜名字
+  pub name_entry: Option<&'a str>,
+}
+
+impl<'a> Screen<'a> {
+  /// Draws the current state; `alpha` blends moving objects between the last two steps.
+  pub fn draw<R: Renderer>(&self, renderer: &mut R, alpha: f32) {
+    match self.state {
+      State::Menu => self.draw_menu(renderer),
+      State::Playing => self.draw_world(renderer, alpha),
+      State::Paused => {
+        self.draw_world(renderer, alpha);
+        self.draw_banner(renderer, "PAUSED", "P TO RESUME - ESC FOR MENU");
+      },
+      State::LevelWon => {
+        self.draw_world(renderer, alpha);
+        let subtitle = if self.world.level_bonus > 0 {
+          format!("TIME BONUS {} - PRESS ENTER", self.world.level_bonus)
+        } else {
+          String::from("PRESS ENTER FOR THE NEXT LEVEL")
+        };
+        self.draw_banner(renderer, "LEVEL CLEARED!", &subtitle);
+      },
+      State::GameOver => {
+        self.draw_world(renderer, alpha);
+        self.draw_final_score(renderer);
+        if !self.draw_name_entry(renderer) {
+          self.draw_banner(renderer, "GAME OVER", "ENTER TO RETRY - ESC FOR MENU");
+        }
+      },
+      State::CampaignComplete => {
+        self.draw_background(renderer);
+        self.draw_final_score(renderer);
+        if !self.draw_name_entry(renderer) {
+          self.draw_banner(renderer, "YOU WIN!", "PRESS ENTER");
+        }
+      },
+      State::HighScores => self.draw_high_scores(renderer),
+    }
+  }
+
+  fn draw_world<R: Renderer>(&self, renderer: &mut R, alpha: f32) {
+    self.draw_background(renderer);
+    for brick in &self.world.bricks {
+      if !brick.is_destroyed {
+        self.draw_object(renderer, &brick.game_object, alpha);
+      }
+    }
+    for power_up in &self.world.power_ups {
+      self.draw_object(renderer, &power_up.game_object, alpha);
+    }
+    self.draw_object(renderer, &self.world.player.game_object, alpha);
+    for ball in &self.world.balls {
+      self.draw_object(renderer, &ball.game_object, alpha);
+    }
+    self.draw_hud(renderer);
+  }
+
+  fn draw_hud<R: Renderer>(&self, renderer: &mut R) {
+    let white = vec3(1.0, 1.0, 1.0);
+    let hud = format!("LIVES: {}  SCORE: {}  X{}", self.world.lives.remaining(), self.world.scoring.score(), self.world.scoring.multiplier());
+    text::draw(renderer, &hud, vec2(5.0, 5.0), 2.0, white);
+  }
+
+  /// Draws the name prompt if a new high score is being entered.
+  fn draw_name_entry<R: Renderer>(&self, renderer: &mut R) -> bool {
+    let name = match self.name_entry {
+      Some(name) => name,
+      None => return false,
+    };
+    let center = WIDTH as f32 / 2.0;
+    let white = vec3(1.0, 1.0, 1.0);
+    self.draw_banner(renderer, "NEW HIGH SCORE!", "TYPE YOUR NAME - ENTER TO SAVE");
+    text::draw_centered(renderer, &format!("{}_", name), center, 500.0, 4.0, white);
+    true
+  }
+
+  fn draw_high_scores<R: Renderer>(&self, renderer: &mut R) {
+    let center = WIDTH as f32 / 2.0;
+    let white = vec3(1.0, 1.0, 1.0);
+    self.draw_background(renderer);
+    text::draw_centered(renderer, "HIGH SCORES", center, 40.0, 6.0, white);
+    if self.high_scores.entries().is_empty() {
+      text::draw_centered(renderer, "NO SCORES YET", center, 260.0, 3.0, white);
+    }
+    let header = format!("   {:<10} {:>7} {:<3} {:<10} {}", "NAME", "SCORE", "LV", "DATE", "SEED");
+    text::draw(renderer, &header, vec2(16.0, 110.0), 2.0, white);
+    for (idx, e) in self.high_scores.entries().iter().enumerate() {
+      let seed = e.seed.map_or(String::from("-"), |seed| seed.to_string());
+      let row = format!("{:>2} {:<10} {:>7} {:<3} {} {}", idx + 1, e.name, e.score, e.level, e.date, seed);
+      text::draw(renderer, &row, vec2(16.0, 150.0 + idx as f32 * 36.0), 2.0, white);
+    }
+    text::draw_centered(renderer, "PRESS ENTER", center, 540.0, 3.0, white);
+  }
+
+  fn draw_final_score<R: Renderer>(&self, renderer: &mut R) {
+    let white = vec3(1.0, 1.0, 1.0);
+    let score = format!("SCORE {}", self.world.scoring.score());
+    text::draw_centered(renderer, &score, WIDTH as f32 / 2.0, 300.0, 4.0, white);
+  }
+
+  fn draw_menu<R: Renderer>(&self, renderer: &mut R) {
+    let center = WIDTH as f32 / 2.0;
+    let white = vec3(1.0, 1.0, 1.0);
+    self.draw_background(renderer);
+    text::draw_centered(renderer, "BREAKOUT", center, 180.0, 10.0, white);
+    text::draw_centered(renderer, "PRESS ENTER TO START", center, 320.0, 3.0, white);
+    text::draw_centered(renderer, "H FOR HIGH SCORES", center, 360.0, 3.0, white);
+    text::draw_centered(renderer, "ESC TO QUIT", center, 400.0, 3.0, white);
+    if let Some(seed) = self.world.campaign.seed() {
+      text::draw_centered(renderer, &format!("SEED {}", seed), center, 540.0, 2.0, white);
+    }
+  }
+
+  fn draw_banner<R: Renderer>(&self, renderer: &mut R, title: &str, subtitle: &str) {
+    let center = WIDTH as f32 / 2.0;
+    let white = vec3(1.0, 1.0, 1.0);
+    text::draw_centered(renderer, title, center, 380.0, 6.0, white);
+    text::draw_centered(renderer, subtitle, center, 450.0, 3.0, white);
+  }
+
+  fn draw_background<R: Renderer>(&self, renderer: &mut R) {
+    let bg = &self.world.background.game_object;
+    renderer.draw_sprite(bg.texture, bg.position, bg.size, 0.0, bg.color);
+  }
+
+  fn draw_object<R: Renderer>(&self, renderer: &mut R, game_object: &GameObject, alpha: f32) {
+    // Chaos 效果：所有物体左右晃动
+    let rotate = if self.world.active_power_ups.is_active(PowerUpKind::Chaos) {
+      (self.world.effect_time * 8.0).sin() * 12.0
+    } else {
+      0.0
+    };
+    let position = game_object.interpolated_position(alpha);
+    renderer.draw_sprite(game_object.texture, position, game_object.size, rotate, game_object.color);
+  }
+}
@@ -1,9 +1,9 @@
 #![allow(non_snake_case)]
 use std::ffi::{CString, CStr};
-use std::fs::File;
-use std::io::Read;
+use std::fmt;
+use std::fs;
+use std::io;
 use std::ptr;
-use std::str;
 
 use gl;
 use gl::types::*;
@@ -15,54 +15,78 @@ pub struct Shader {
     pub ID: u32,
 }
 
+#[derive(Debug)]
+pub enum ShaderError {
+    Io(String, io::Error),
+    // 带着 GL 给出的 info log
+    Compile { path: String, log: String },
+    Link { vertex: String, fragment: String, log: String },
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShaderError::Io(path, e) => write!(f, "{}: {}", path, e),
+            ShaderError::Compile { path, log } => write!(f, "{}: failed to compile:\n{}", path, log),
+            ShaderError::Link { vertex, fragment, log } =>
+                write!(f, "{} + {}: failed to link:\n{}", vertex, fragment, log),
+        }
+    }
+}
+
 /// NOTE: mixture of `shader_s.h` and `shader_m.h` (the latter just contains
 /// a few more setters for uniforms)
 #[allow(dead_code)]
 impl Shader {
-    pub fn new(vertexPath: &str, fragmentPath: &str) -> Shader {
-        let mut shader = Shader { ID: 0 };
+    pub fn new(vertexPath: &str, fragmentPath: &str) -> Result<Shader, ShaderError> {
         // 1. retrieve the vertex/fragment source code from filesystem
-        let mut vShaderFile = File::open(vertexPath)
-            .unwrap_or_else(|e| panic!("Failed to open {}: {}", vertexPath, e));
-        let mut fShaderFile = File::open(fragmentPath)
-            .unwrap_or_else(|e| panic!("Failed to open {}: {}", fragmentPath, e));
-        let mut vertexCode = String::new();
-        let mut fragmentCode = String::new();
-        vShaderFile
-            .read_to_string(&mut vertexCode)
-            .expect("Failed to read vertex shader");
-        fShaderFile
-            .read_to_string(&mut fragmentCode)
-            .expect("Failed to read fragment shader");
+        let read = |path: &str| fs::read_to_string(path).map_err(|e| ShaderError::Io(path.to_string(), e));
+        let vertexCode = read(vertexPath)?;
+        let fragmentCode = read(fragmentPath)?;
+
+        Shader::from_source(vertexPath, &vertexCode, fragmentPath, &fragmentCode)
+    }
 
-        let vShaderCode = CString::new(vertexCode.as_bytes()).unwrap();
-        let fShaderCode = CString::new(fragmentCode.as_bytes()).unwrap();
+    /// Compiles and links a program from vertex/fragment source already in memory.
+    pub fn from_source(vertexLabel: &str, vertexCode: &str, fragmentLabel: &str, fragmentCode: &str) -> Result<Shader, ShaderError> {
+        // 源码中间有 \0 时 GL 也只会看到前半段，当作编译错误
+        let cSource = |path: &str, code: &str| CString::new(code.as_bytes()).map_err(|_| ShaderError::Compile {
+            path: path.to_string(),
+            log: String::from("source contains a NUL byte"),
+        });
+        let vShaderCode = cSource(vertexLabel, vertexCode)?;
+        let fShaderCode = cSource(fragmentLabel, fragmentCode)?;
 
         // 2. compile shaders
         unsafe {
             // vertex shader
-            let vertex = gl::CreateShader(gl::VERTEX_SHADER);
-            gl::ShaderSource(vertex, 1, &vShaderCode.as_ptr(), ptr::null());
-            gl::CompileShader(vertex);
-            shader.checkCompileErrors(vertex, "VERTEX");
+            let vertex = Shader::compile(gl::VERTEX_SHADER, vertexLabel, &vShaderCode)?;
             // fragment Shader
-            let fragment = gl::CreateShader(gl::FRAGMENT_SHADER);
-            gl::ShaderSource(fragment, 1, &fShaderCode.as_ptr(), ptr::null());
-            gl::CompileShader(fragment);
-            shader.checkCompileErrors(fragment, "FRAGMENT");
+            let fragment = match Shader::compile(gl::FRAGMENT_SHADER, fragmentLabel, &fShaderCode) {
+                Ok(fragment) => fragment,
+                Err(e) => {
+                    gl::DeleteShader(vertex);
+                    return Err(e);
+                }
+            };
             // shader Program
             let ID = gl::CreateProgram();
             gl::AttachShader(ID, vertex);
             gl::AttachShader(ID, fragment);
             gl::LinkProgram(ID);
-            shader.checkCompileErrors(ID, "PROGRAM");
             // delete the shaders as they're linked into our program now and no longer necessary
             gl::DeleteShader(vertex);
             gl::DeleteShader(fragment);
-            shader.ID = ID;
-        }
 
-        shader
+            let mut success = gl::FALSE as GLint;
+            gl::GetProgramiv(ID, gl::LINK_STATUS, &mut success);
+            if success != gl::TRUE as GLint {
+                let log = Shader::infoLog(ID, gl::GetProgramInfoLog);
+                gl::DeleteProgram(ID);
+                return Err(ShaderError::Link { vertex: vertexLabel.to_string(), fragment: fragmentLabel.to_string(), log });
+            }
+            Ok(Shader { ID })
+        }
     }
 
     /// activate the shader
@@ -93,98 +117,37 @@ impl Shader {
         gl::Uniform3f(gl::GetUniformLocation(self.ID, name.as_ptr()), x, y, z);
     }
     /// ------------------------------------------------------------------------
+    pub unsafe fn setVec4(&self, name: &CStr, x: f32, y: f32, z: f32, w: f32) {
+        gl::Uniform4f(gl::GetUniformLocation(self.ID, name.as_ptr()), x, y, z, w);
+    }
+    /// ------------------------------------------------------------------------
     pub unsafe fn setMat4(&self, name: &CStr, mat: &Matrix4<f32>) {
         gl::UniformMatrix4fv(gl::GetUniformLocation(self.ID, name.as_ptr()), 1, gl::FALSE, mat.as_ptr());
     }
 
-    /// utility function for checking shader compilation/linking errors.
+    /// compiles one stage, returning the GL info log as the error
     /// ------------------------------------------------------------------------
-    unsafe fn checkCompileErrors(&self, shader: u32, type_: &str) {
+    unsafe fn compile(type_: GLenum, path: &str, code: &CStr) -> Result<u32, ShaderError> {
+        let shader = gl::CreateShader(type_);
+        gl::ShaderSource(shader, 1, &code.as_ptr(), ptr::null());
+        gl::CompileShader(shader);
         let mut success = gl::FALSE as GLint;
-        let mut infoLog = Vec::with_capacity(1024);
-        infoLog.set_len(1024 - 1); // subtract 1 to skip the trailing null character
-        if type_ != "PROGRAM" {
-            gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut success);
-            if success != gl::TRUE as GLint {
-                gl::GetShaderInfoLog(shader, 1024, ptr::null_mut(), infoLog.as_mut_ptr() as *mut GLchar);
-                println!("ERROR::SHADER_COMPILATION_ERROR of type: {}\n{}\n \
-                          -- --------------------------------------------------- -- ",
-                         type_,
-                         str::from_utf8(&infoLog).unwrap());
-            }
-
-        } else {
-            gl::GetProgramiv(shader, gl::LINK_STATUS, &mut success);
-            if success != gl::TRUE as GLint {
-                gl::GetProgramInfoLog(shader, 1024, ptr::null_mut(), infoLog.as_mut_ptr() as *mut GLchar);
-                println!("ERROR::PROGRAM_LINKING_ERROR of type: {}\n{}\n \
-                          -- --------------------------------------------------- -- ",
-                         type_,
-                         str::from_utf8(&infoLog).unwrap());
-            }
+        gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut success);
+        if success != gl::TRUE as GLint {
+            let log = Shader::infoLog(shader, gl::GetShaderInfoLog);
+            gl::DeleteShader(shader);
+            return Err(ShaderError::Compile { path: path.to_string(), log });
         }
-
+        Ok(shader)
     }
 
-    /// Only used in 4.9 Geometry shaders - ignore until then (shader.h in original C++)
-    pub fn with_geometry_shader(vertexPath: &str, fragmentPath: &str, geometryPath: &str) -> Self {
-        let mut shader = Shader { ID: 0 };
-        // 1. retrieve the vertex/fragment source code from filesystem
-        let mut vShaderFile = File::open(vertexPath)
-            .unwrap_or_else(|_| panic!("Failed to open {}", vertexPath));
-        let mut fShaderFile = File::open(fragmentPath)
-            .unwrap_or_else(|_| panic!("Failed to open {}", fragmentPath));
-        let mut gShaderFile = File::open(geometryPath)
-            .unwrap_or_else(|_| panic!("Failed to open {}", geometryPath));
-        let mut vertexCode = String::new();
-        let mut fragmentCode = String::new();
-        let mut geometryCode = String::new();
-        vShaderFile
-            .read_to_string(&mut vertexCode)
-            .expect("Failed to read vertex shader");
-        fShaderFile
-            .read_to_string(&mut fragmentCode)
-            .expect("Failed to read fragment shader");
-        gShaderFile
-            .read_to_string(&mut geometryCode)
-            .expect("Failed to read geometry shader");
-
-        let vShaderCode = CString::new(vertexCode.as_bytes()).unwrap();
-        let fShaderCode = CString::new(fragmentCode.as_bytes()).unwrap();
-        let gShaderCode = CString::new(geometryCode.as_bytes()).unwrap();
-
-        // 2. compile shaders
-        unsafe {
-            // vertex shader
-            let vertex = gl::CreateShader(gl::VERTEX_SHADER);
-            gl::ShaderSource(vertex, 1, &vShaderCode.as_ptr(), ptr::null());
-            gl::CompileShader(vertex);
-            shader.checkCompileErrors(vertex, "VERTEX");
-            // fragment Shader
-            let fragment = gl::CreateShader(gl::FRAGMENT_SHADER);
-            gl::ShaderSource(fragment, 1, &fShaderCode.as_ptr(), ptr::null());
-            gl::CompileShader(fragment);
-            shader.checkCompileErrors(fragment, "FRAGMENT");
-            // geometry shader
-            let geometry = gl::CreateShader(gl::GEOMETRY_SHADER);
-            gl::ShaderSource(geometry, 1, &gShaderCode.as_ptr(), ptr::null());
-            gl::CompileShader(geometry);
-            shader.checkCompileErrors(geometry, "GEOMETRY");
-
-            // shader Program
-            let ID = gl::CreateProgram();
-            gl::AttachShader(ID, vertex);
-            gl::AttachShader(ID, fragment);
-            gl::AttachShader(ID, geometry);
-            gl::LinkProgram(ID);
-            shader.checkCompileErrors(ID, "PROGRAM");
-            // delete the shaders as they're linked into our program now and no longer necessary
-            gl::DeleteShader(vertex);
-            gl::DeleteShader(fragment);
-            gl::DeleteShader(geometry);
-            shader.ID = ID;
-        }
-
-        shader
+    /// reads a shader or program info log with `getLog`
+    /// ------------------------------------------------------------------------
+    unsafe fn infoLog(object: u32, getLog: unsafe fn(GLuint, GLsizei, *mut GLsizei, *mut GLchar)) -> String {
+        let mut infoLog = vec![0u8; 1024];
+        let mut length = 0;
+        getLog(object, infoLog.len() as GLsizei, &mut length, infoLog.as_mut_ptr() as *mut GLchar);
+        infoLog.truncate(length.max(0) as usize);
+        String::from_utf8_lossy(&infoLog).trim_end().to_string()
     }
 }
@@ -0,0 +1,214 @@
+extern crate image;
+use std::io;
+use std::rc::Rc;
+use std::path::Path;
+use cgmath::{vec2, vec4, SquareMatrix, Vector2, Vector3, Vector4};
+use image::{Rgba, RgbaImage};
+use crate::assets::Assets;
+use crate::atlas::Atlas;
+use crate::game_object;
+use crate::renderer::{self, Renderer, UvRect};
+use crate::resources::ResourceManager;
+use crate::texture_data::TextureData;
+use crate::texture_options::{Filter, TextureOptions, Wrap};
+
+/// Renders on the CPU into an RGBA buffer, following the GL path step by step:
+/// the model and projection matrices, atlas and decoded textures `Sprite` uses,
+/// `sprite.fs`'s `spriteColor * texture` sampled with each texture's wrap mode
+/// and filters, and `SRC_ALPHA, ONE_MINUS_SRC_ALPHA` blending (`ONE, ...` for
+/// premultiplied textures). Mipmaps and anisotropic filtering are not
+/// emulated. Needs no GPU, so frames can be rendered and saved as PNG anywhere.
+pub struct SoftwareRenderer {
+  frame: RgbaImage,
+  atlas: Atlas,
+  atlas_texture: Rc<SoftwareTexture>,
+  // 不在图集里的纹理
+  textures: ResourceManager<SoftwareTexture>,
+}
+
+/// A texture's pixels plus the options that change how GL would sample and blend them.
+pub struct SoftwareTexture {
+  pub image: RgbaImage,
+  pub options: TextureOptions,
+}
+
+impl SoftwareTexture {
+  /// `srgb` and `premultiplied` follow `data`, like `Texture::from_data` does.
+  pub fn new(data: &TextureData, options: &TextureOptions) -> SoftwareTexture {
+    let options = options.srgb(data.srgb).premultiplied(data.premultiplied);
+    SoftwareTexture { image: data.to_rgba(), options }
+  }
+}
+
+impl SoftwareRenderer {
+  /// A black `width` x `height` frame with every game texture loaded from the embedded assets.
+  pub fn new(width: u32, height: u32) -> SoftwareRenderer {
+    Self::with_assets(width, height, Assets::default())
+  }
+
+  /// Like `new`, but textures come from `assets`, e.g. to render with a mod's images.
+  pub fn with_assets(width: u32, height: u32, assets: Assets) -> SoftwareRenderer {
+    let atlas = Atlas::from_assets(&assets);
+    let atlas_texture = Rc::new(SoftwareTexture {
+      image: atlas.image().clone(),
+      options: TextureOptions::new().wrap(Wrap::ClampToEdge),
+    });
+    let mut renderer = SoftwareRenderer {
+      frame: RgbaImage::new(width, height),
+      atlas,
+      atlas_texture,
+      textures: ResourceManager::new(move |key| {
+        SoftwareTexture::new(&assets.texture_or_missing(key), &assets.texture_options(key))
+      }),
+    };
+    for &src in game_object::TEXTURES.iter() {
+      if renderer.atlas.region(src).is_none() {
+        renderer.textures.get(src);
+      }
+    }
+    renderer
+  }
+
+  /// Adds or replaces the texture drawn for `key`. It's drawn on its own, not from the atlas.
+  pub fn add_texture(&mut self, key: &str, img: RgbaImage) {
+    self.atlas.remove(key);
+    self.textures.insert(key, SoftwareTexture { image: img, options: TextureOptions::new() });
+  }
+
+  /// The atlas most textures are drawn from.
+  pub fn atlas(&self) -> &Atlas {
+    &self.atlas
+  }
+
+  /// The textures loaded on their own, e.g. to check how often each was loaded.
+  pub fn textures(&self) -> &ResourceManager<SoftwareTexture> {
+    &self.textures
+  }
+
+  /// The frame as drawn so far.
+  pub fn image(&self) -> &RgbaImage {
+    &self.frame
+  }
+
+  /// Writes the frame as a PNG (or whatever format `path`'s extension names).
+  pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+    self.frame.save(path)
+  }
+
+  /// Pixel coordinates of a clip space position, rows counted from the top like the PNG.
+  fn to_window(&self, clip: Vector4<f32>) -> (f32, f32) {
+    let (width, height) = (self.frame.width() as f32, self.frame.height() as f32);
+    ((clip.x / clip.w + 1.0) / 2.0 * width, (1.0 - clip.y / clip.w) / 2.0 * height)
+  }
+}
+
+/// Texel index `i` of a `size` texel row or column after applying `wrap`.
+fn wrap_texel(i: i64, size: i64, wrap: Wrap) -> u32 {
+  let i = match wrap {
+    Wrap::Repeat => i.rem_euclid(size),
+    Wrap::ClampToEdge => i.max(0).min(size - 1),
+    Wrap::MirroredRepeat => {
+      let i = i.rem_euclid(2 * size);
+      if i < size { i } else { 2 * size - 1 - i }
+    },
+  };
+  i as u32
+}
+
+/// Samples like GL with `filter` and the texture's wrap mode; `u`/`v` are texture
+/// coordinates. sRGB texels are converted to linear before filtering, as GL does.
+fn sample(texture: &SoftwareTexture, u: f32, v: f32, filter: Filter) -> Vector4<f32> {
+  let image = &texture.image;
+  let wrap = texture.options.wrap;
+  let (width, height) = (image.width() as i64, image.height() as i64);
+  let texel = |tx: i64, ty: i64| {
+    let p = image.get_pixel(wrap_texel(tx, width, wrap), wrap_texel(ty, height, wrap));
+    let c = vec4(p[0] as f32, p[1] as f32, p[2] as f32, p[3] as f32) / 255.0;
+    if texture.options.srgb {
+      vec4(srgb_to_linear(c.x), srgb_to_linear(c.y), srgb_to_linear(c.z), c.w)
+    } else {
+      c
+    }
+  };
+  if filter == Filter::Nearest {
+    return texel((u * width as f32).floor() as i64, (v * height as f32).floor() as i64);
+  }
+  let x = u * width as f32 - 0.5;
+  let y = v * height as f32 - 0.5;
+  let (x0, y0) = (x.floor(), y.floor());
+  let (fx, fy) = (x - x0, y - y0);
+  let (x0, y0) = (x0 as i64, y0 as i64);
+  let top = texel(x0, y0) * (1.0 - fx) + texel(x0 + 1, y0) * fx;
+  let bottom = texel(x0, y0 + 1) * (1.0 - fx) + texel(x0 + 1, y0 + 1) * fx;
+  top * (1.0 - fy) + bottom * fy
+}
+
+fn srgb_to_linear(c: f32) -> f32 {
+  if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+}
+
+fn to_u8(c: f32) -> u8 {
+  (c.max(0.0).min(1.0) * 255.0).round() as u8
+}
+
+impl Renderer for SoftwareRenderer {
+  fn clear(&mut self, color: Vector3<f32>) {
+    let pixel = Rgba([to_u8(color.x), to_u8(color.y), to_u8(color.z), 255]);
+    for p in self.frame.pixels_mut() {
+      *p = pixel;
+    }
+  }
+
+  fn draw_sprite(&mut self, texture: &str, position: Vector2<f32>, size: Vector2<f32>, rotate: f32, color: Vector3<f32>) {
+    let (width, height) = (self.frame.width(), self.frame.height());
+    let to_clip = renderer::projection_matrix(width, height) * renderer::model_matrix(position, size, rotate);
+    let to_local = match to_clip.invert() {
+      Some(m) => m,
+      // 尺寸为 0 的精灵什么也不画
+      None => return,
+    };
+
+    // 四个角变换到窗口坐标后的包围盒
+    let corners = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)];
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (std::f32::INFINITY, std::f32::INFINITY, std::f32::NEG_INFINITY, std::f32::NEG_INFINITY);
+    for &(x, y) in corners.iter() {
+      let (wx, wy) = self.to_window(to_clip * vec4(x, y, 0.0, 1.0));
+      min_x = min_x.min(wx);
+      min_y = min_y.min(wy);
+      max_x = max_x.max(wx);
+      max_y = max_y.max(wy);
+    }
+    let (texture, uv) = match self.atlas.uv(texture) {
+      Some(uv) => (Rc::clone(&self.atlas_texture), uv),
+      None => (self.textures.get(texture), UvRect::FULL),
+    };
+    let (x_start, x_end) = (min_x.floor().max(0.0) as u32, (max_x.ceil().max(0.0) as u32).min(width));
+    let (y_start, y_end) = (min_y.floor().max(0.0) as u32, (max_y.ceil().max(0.0) as u32).min(height));
+    // 纹素比屏幕像素密时是缩小，GL 用 min filter，否则用 mag filter
+    let texels = vec2(texture.image.width() as f32 * (uv.max.x - uv.min.x), texture.image.height() as f32 * (uv.max.y - uv.min.y));
+    let texels_per_pixel = (texels.x / size.x.abs()).max(texels.y / size.y.abs());
+    let filter = if texels_per_pixel > 1.0 { texture.options.min_filter } else { texture.options.mag_filter };
+
+    for py in y_start..y_end {
+      for px in x_start..x_end {
+        // 像素中心反变换回精灵的本地坐标，再映射到纹理区域
+        let ndc_x = (px as f32 + 0.5) / width as f32 * 2.0 - 1.0;
+        let ndc_y = 1.0 - (py as f32 + 0.5) / height as f32 * 2.0;
+        let local = to_local * vec4(ndc_x, ndc_y, 0.0, 1.0);
+        let (u, v) = (local.x / local.w, local.y / local.w);
+        if !(0.0..1.0).contains(&u) || !(0.0..1.0).contains(&v) {
+          continue;
+        }
+        let uv = uv.lerp(vec2(u, v));
+        let src = sample(&texture, uv.x, uv.y, filter);
+        let (r, g, b, a) = (src.x * color.x, src.y * color.y, src.z * color.z, src.w);
+        let dst = self.frame.get_pixel_mut(px, py);
+        let src_factor = if texture.options.premultiplied { 1.0 } else { a };
+        let blend = |s: f32, d: u8| to_u8(s * src_factor + d as f32 / 255.0 * (1.0 - a));
+        *dst = Rgba([blend(r, dst[0]), blend(g, dst[1]), blend(b, dst[2]), blend(a, dst[3])]);
+      }
+    }
+  }
+
+  fn present(&mut self) {}
+}
@@ -1,8 +1,14 @@
-use crate::shader::Shader;
+use crate::shader::{Shader, ShaderError};
 use crate::texture::Texture;
 extern crate gl;
 use self::gl::types::*;
-use cgmath::{Matrix4, vec3, Vector2, Vector3, Deg, ortho};
+use cgmath::{Vector2, Vector3};
+use breakout::assets::Assets;
+use breakout::atlas::Atlas;
+use breakout::game_object;
+use breakout::renderer::{self, Renderer, UvRect};
+use breakout::resources::ResourceManager;
+use breakout::texture_options::{TextureOptions, Wrap};
 use std::os::raw::c_void;
 use std::ptr;
 use std::mem;
@@ -13,11 +19,17 @@ pub struct Sprite {
   vao: GLuint,
   window_width: u32,
   window_height: u32,
+  // 大部分纹理打包在图集里，共用一个 GL 纹理
+  atlas: Atlas,
+  atlas_texture: Texture,
+  // 不在图集里的纹理，按纹理名索引，见 Renderer
+  textures: ResourceManager<Texture>,
 }
 
 impl Sprite {
-  pub fn new(window_width: u32, window_height: u32) -> Sprite {
-    let shader = Shader::new("src/sprite.vs", "src/sprite.fs");
+  pub fn new(window_width: u32, window_height: u32, assets: &Assets) -> Result<Sprite, ShaderError> {
+    let source = |key: &str| assets.read_to_string(key).map_err(|e| ShaderError::Io(key.to_string(), e));
+    let shader = Shader::from_source("sprite.vs", &source("sprite.vs")?, "sprite.fs", &source("sprite.fs")?)?;
     
     let vertices: [f32; 24] = [
       // 位置     // 纹理
@@ -51,34 +63,74 @@ impl Sprite {
       gl::BindVertexArray(0);
     }
 
-    Sprite {
+    let atlas = Atlas::from_assets(assets);
+    let atlas_options = TextureOptions::new().wrap(Wrap::ClampToEdge);
+    let atlas_texture = Texture::from_rgba(atlas.image().width(), atlas.image().height(), atlas.image(), &atlas_options);
+
+    let assets = assets.clone();
+    let mut textures = ResourceManager::new(move |key| {
+      Texture::from_data(&assets.texture_or_missing(key), &assets.texture_options(key))
+    });
+    for &src in game_object::TEXTURES.iter().filter(|&&src| atlas.region(src).is_none()) {
+      textures.get(src);
+    }
+
+    Ok(Sprite {
       window_width,
       window_height,
       shader,
-      vao
-    }
+      vao,
+      atlas,
+      atlas_texture,
+      textures,
+    })
   }
-  pub fn draw(&self, texture: &Texture, position: Vector2<f32>, size: Vector2<f32>, rotate: f32, color: Vector3<f32>) {
+
+  /// Frees the textures outside the atlas that nothing is drawing with any more.
+  /// They are loaded again the next time they're drawn.
+  pub fn release_unused(&mut self) -> usize {
+    self.textures.release_unused()
+  }
+
+  /// Draws the `uv` part of `texture`; `UvRect::FULL` draws all of it.
+  pub fn draw(&self, texture: &Texture, uv: UvRect, position: Vector2<f32>, size: Vector2<f32>, rotate: f32, color: Vector3<f32>) {
     unsafe {
       self.shader.useProgram();
-      // 缩放
-      let mut model = Matrix4::from_nonuniform_scale(size.x, size.y, 1.0);
-      // 将中心移动到原点
-      model = Matrix4::from_translation(vec3(-0.5 * size.x, -0.5 * size.y, 1.0)) * model;
-      // 绕原点旋转
-      model = Matrix4::from_angle_z(Deg(rotate)) * model;
-      // 移动
-      model = Matrix4::from_translation(vec3(position.x + 0.5 * size.x, position.y + 0.5 * size.y, 0.0)) * model;
-
-      let projection = ortho(0.0, self.window_width as f32, self.window_height as f32, 0.0, -1.0, 1.0);
+      let model = renderer::model_matrix(position, size, rotate);
+      let projection = renderer::projection_matrix(self.window_width, self.window_height);
 
       self.shader.setMat4(c_str!("model"), &model);
       self.shader.setMat4(c_str!("projection"), &projection);
       self.shader.setVector3(c_str!("spriteColor"), &color);
+      self.shader.setVec4(c_str!("uvRect"), uv.min.x, uv.min.y, uv.max.x, uv.max.y);
+      let src_factor = if texture.premultiplied { gl::ONE } else { gl::SRC_ALPHA };
+      gl::BlendFunc(src_factor, gl::ONE_MINUS_SRC_ALPHA);
       texture.bind();
       gl::BindVertexArray(self.vao);
       gl::DrawArrays(gl::TRIANGLES, 0, 6);
       gl::BindVertexArray(0);
     }
   }
-}
\ No newline at end of file
+}
+
+impl Renderer for Sprite {
+  fn clear(&mut self, color: Vector3<f32>) {
+    unsafe {
+      gl::ClearColor(color.x, color.y, color.z, 1.0);
+      gl::Clear(gl::COLOR_BUFFER_BIT);
+    }
+  }
+
+  fn draw_sprite(&mut self, texture: &str, position: Vector2<f32>, size: Vector2<f32>, rotate: f32, color: Vector3<f32>) {
+    match self.atlas.uv(texture) {
+      Some(uv) => self.draw(&self.atlas_texture, uv, position, size, rotate, color),
+      None => {
+        let texture = self.textures.get(texture);
+        self.draw(&texture, UvRect::FULL, position, size, rotate, color);
+      },
+    }
+  }
+
+  // 交换缓冲区由窗口负责
+  fn present(&mut self) {}
+}
@@ -5,9 +5,11 @@ out vec2 TexCoords;
 
 uniform mat4 model;
 uniform mat4 projection;
+// 要画的纹理区域：xy 为左上角，zw 为右下角
+uniform vec4 uvRect;
 
 void main()
 {
-    TexCoords = vertex.zw;
+    TexCoords = mix(uvRect.xy, uvRect.zw, vertex.zw);
     gl_Position = projection * model * vec4(vertex.xy, 0.0, 1.0);
 }
\ No newline at end of file
@@ -0,0 +1,158 @@
+use crate::world::{World, Input};
+
+/// Screens the game can be on. Only `Playing` runs the simulation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum State {
+  Menu,
+  Playing,
+  Paused,
+  LevelWon,
+  GameOver,
+  CampaignComplete,
+  HighScores,
+}
+
+/// Everything that can move the game between states: player input
+/// (`Confirm`, `Pause`, `Back`, `ShowHighScores`) and outcomes reported by the simulation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Event {
+  Confirm,
+  Pause,
+  Back,
+  ShowHighScores,
+  LevelCleared,
+  CampaignCleared,
+  OutOfLives,
+}
+
+/// A change from one state to another, see `State::handle`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transition {
+  pub from: State,
+  pub to: State,
+}
+
+impl Transition {
+  /// Whether the world was reset to the start of a level.
+  pub fn started_level(self) -> bool {
+    self.to == State::Playing && self.from != State::Paused
+  }
+
+  /// Whether the run just ended, won or lost.
+  pub fn ended_run(self) -> bool {
+    self.to == State::GameOver || self.to == State::CampaignComplete
+  }
+}
+
+impl State {
+  /// The state `event` leads to, or `None` if the event means nothing here.
+  pub fn next(self, event: Event) -> Option<State> {
+    match (self, event) {
+      (State::Menu, Event::Confirm) => Some(State::Playing),
+      (State::Menu, Event::ShowHighScores) => Some(State::HighScores),
+
+      (State::HighScores, Event::Confirm) | (State::HighScores, Event::Back) => Some(State::Menu),
+
+      (State::Playing, Event::Pause) | (State::Playing, Event::Back) => Some(State::Paused),
+      (State::Playing, Event::LevelCleared) => Some(State::LevelWon),
+      (State::Playing, Event::CampaignCleared) => Some(State::CampaignComplete),
+      (State::Playing, Event::OutOfLives) => Some(State::GameOver),
+
+      (State::Paused, Event::Pause) | (State::Paused, Event::Confirm) => Some(State::Playing),
+      (State::Paused, Event::Back) => Some(State::Menu),
+
+      (State::LevelWon, Event::Confirm) => Some(State::Playing),
+
+      (State::GameOver, Event::Confirm) => Some(State::Playing),
+      (State::GameOver, Event::Back) => Some(State::Menu),
+      (State::CampaignComplete, Event::Confirm) | (State::CampaignComplete, Event::Back) => Some(State::Menu),
+
+      _ => None,
+    }
+  }
+
+  /// Moves to the state `event` leads to, setting `world` up for it: a new run
+  /// from the menu, the next level after a win, a retry after a game over.
+  /// Returns `None` and stays put if the event means nothing here.
+  pub fn handle(&mut self, event: Event, world: &mut World) -> Option<Transition> {
+    let next = self.next(event)?;
+    match (*self, next) {
+      (State::Menu, State::Playing) => {
+        world.campaign.restart();
+        world.new_run();
+        world.reset();
+      },
+      (State::LevelWon, State::Playing) => world.reset(),
+      // 重试当前关卡
+      (State::GameOver, State::Playing) => {
+        world.new_run();
+        world.reset();
+      },
+      _ => {}
+    }
+    let transition = Transition { from: *self, to: next };
+    *self = next;
+    Some(transition)
+  }
+
+  /// Advances the game by one fixed step of `dt` seconds. Only `Playing` runs the
+  /// simulation; returns the transition if the step ended the level or the run.
+  pub fn update(&mut self, world: &mut World, dt: f32, input: Input) -> Option<Transition> {
+    // 不在游戏中时也同步一次，让暂停等画面里的物体保持静止
+    world.save_previous_positions();
+    if *self != State::Playing {
+      return None;
+    }
+    let event = world.step(dt, input)?;
+    self.handle(event, world)
+  }
+
+  /// Whether `event` closes the game instead: `Back` on the menu.
+  pub fn quits_on(self, event: Event) -> bool {
+    self == State::Menu && event == Event::Back
+  }
+
+  /// Whether the playfield is drawn behind this screen.
+  pub fn shows_world(self) -> bool {
+    match self {
+      State::Playing | State::Paused | State::LevelWon | State::GameOver => true,
+      State::Menu | State::CampaignComplete | State::HighScores => false,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn next_follows_a_whole_run() {
+    let mut state = State::Menu;
+    for &(event, expected) in &[
+      (Event::Confirm, State::Playing),
+      (Event::Pause, State::Paused),
+      (Event::Pause, State::Playing),
+      (Event::LevelCleared, State::LevelWon),
+      (Event::Confirm, State::Playing),
+      (Event::OutOfLives, State::GameOver),
+      (Event::Back, State::Menu),
+    ] {
+      state = state.next(event).unwrap_or_else(|| panic!("{:?} means nothing on {:?}", event, state));
+      assert_eq!(state, expected);
+    }
+  }
+
+  #[test]
+  fn meaningless_events_change_nothing() {
+    assert_eq!(State::Menu.next(Event::Pause), None);
+    assert_eq!(State::Menu.next(Event::LevelCleared), None);
+    assert_eq!(State::LevelWon.next(Event::Pause), None);
+  }
+
+  #[test]
+  fn back_on_the_menu_quits() {
+    assert!(State::Menu.quits_on(Event::Back));
+    assert!(!State::Playing.quits_on(Event::Back));
+    assert!(!State::Menu.quits_on(Event::Confirm));
+  }
+}
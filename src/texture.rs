@@ -1,68 +1,89 @@
-extern crate image;
-use std::path::Path;
 use std::os::raw::c_void;
-use std::fs::File;
-use std::io::{ Seek, SeekFrom, Read, BufReader };
+use gl::types::{GLenum, GLint};
+use breakout::texture_data::TextureData;
+use breakout::texture_options::{Filter, TextureOptions, Wrap};
+
+// GL_EXT_texture_filter_anisotropic，gl crate 的绑定里没有
+const TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FE;
+const MAX_TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FF;
 
 pub struct Texture {
-  pub id: u32
+  pub id: u32,
+  pub premultiplied: bool,
 }
 
 impl Texture {
-  pub fn new(src: &str) -> Texture {
+  /// Uploads decoded pixels; `options` sets wrapping, filtering and mipmaps.
+  pub fn from_data(texture: &TextureData, options: &TextureOptions) -> Texture {
+    let (internal_format, format) = match (texture.has_alpha, texture.srgb) {
+      (true, true) => (gl::SRGB8_ALPHA8, gl::RGBA),
+      (true, false) => (gl::RGBA8, gl::RGBA),
+      (false, true) => (gl::SRGB8, gl::RGB),
+      (false, false) => (gl::RGB8, gl::RGB),
+    };
+    let mut result = Self::upload(texture.width, texture.height, internal_format, format, &texture.data, options);
+    result.premultiplied = texture.premultiplied;
+    result
+  }
+
+  /// Texture from raw RGBA8 pixels, row by row.
+  pub fn from_rgba(width: u32, height: u32, data: &[u8], options: &TextureOptions) -> Texture {
+    Self::upload(width, height, gl::RGBA8, gl::RGBA, data, options)
+  }
+
+  fn upload(width: u32, height: u32, internal_format: GLenum, format: GLenum, data: &[u8], options: &TextureOptions) -> Texture {
+    let wrap = match options.wrap {
+      Wrap::Repeat => gl::REPEAT,
+      Wrap::ClampToEdge => gl::CLAMP_TO_EDGE,
+      Wrap::MirroredRepeat => gl::MIRRORED_REPEAT,
+    };
+    // 有 mipmap 时缩小过滤在两级 mipmap 之间线性插值
+    let min_filter = match (options.min_filter, options.mipmaps) {
+      (Filter::Nearest, false) => gl::NEAREST,
+      (Filter::Linear, false) => gl::LINEAR,
+      (Filter::Nearest, true) => gl::NEAREST_MIPMAP_LINEAR,
+      (Filter::Linear, true) => gl::LINEAR_MIPMAP_LINEAR,
+    };
+    let mag_filter = match options.mag_filter {
+      Filter::Nearest => gl::NEAREST,
+      Filter::Linear => gl::LINEAR,
+    };
     let mut id = 0;
     unsafe {
       gl::GenTextures(1, &mut id);
       gl::BindTexture(gl::TEXTURE_2D, id); // all upcoming GL_TEXTURE_2D operations now have effect on this texture object
       // set the texture wrapping parameters
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32); // set texture wrapping to gl::REPEAT (default wrapping method)
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
+      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, wrap as GLint);
+      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, wrap as GLint);
       // set texture filtering parameters
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
-      // load image, create texture and generate mipmaps
-      let path = Path::new(src);
-      let fin = File::open(path).unwrap_or_else(|e| panic!("Failed to open file: {}", e));
-      let mut fin = BufReader::new(fin);
-
-      // 取前12个字节，根据文件签名获取文件格式
-      // reference: https://en.wikipedia.org/wiki/List_of_file_signatures
-      let mut prefix_bytes: [u8; 12] = [0; 12];
-      fin.read_exact(&mut prefix_bytes).unwrap();
-      // 重置指针
-      fin.seek(SeekFrom::Start(0)).unwrap();
-      let image_format = image::guess_format(&prefix_bytes).expect(&format!("Failed to guess_format: {}", src)[..]);
-
-      let is_rgba = match image_format {
-        image::ImageFormat::PNG => true,
-        image::ImageFormat::JPEG => false,
-        _ => false
-      };
-
-      let dyn_img = image::load(fin, image_format).expect(&format!("Failed to load texture: {}", src)[..]);
-
-      let format = if is_rgba { gl::RGBA } else { gl::RGB };
-
-      let (width, height, data) = if is_rgba {
-        let img = dyn_img.to_rgba();
-        (img.width(), img.height(), img.to_vec())
-      } else { 
-        let img = dyn_img.to_rgb();
-        (img.width(), img.height(), img.to_vec())
-      };
+      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, min_filter as GLint);
+      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, mag_filter as GLint);
+      if options.anisotropy > 1.0 {
+        // 不支持该扩展时查询失败，max 保持 0，跳过
+        let mut max = 0.0;
+        gl::GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY, &mut max);
+        if max >= 1.0 {
+          gl::TexParameterf(gl::TEXTURE_2D, TEXTURE_MAX_ANISOTROPY, options.anisotropy.min(max));
+        }
+      }
+      // RGB 数据宽度为奇数时行不是 4 字节对齐的
+      gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
       gl::TexImage2D(gl::TEXTURE_2D,
                       0,
-                      format as i32,
+                      internal_format as i32,
                       width as i32,
                       height as i32,
                       0,
                       format,
                       gl::UNSIGNED_BYTE,
                       &data[0] as *const u8 as *const c_void);
-      gl::GenerateMipmap(gl::TEXTURE_2D);
+      gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+      if options.mipmaps {
+        gl::GenerateMipmap(gl::TEXTURE_2D);
+      }
       gl::BindTexture(gl::TEXTURE_2D, 0);
     }
-    Texture { id }
+    Texture { id, premultiplied: false }
   }
 
   pub fn bind(&self) {
@@ -71,5 +92,12 @@ impl Texture {
       gl::BindTexture(gl::TEXTURE_2D, self.id);
     }
   }
+}
 
-}
\ No newline at end of file
+impl Drop for Texture {
+  fn drop(&mut self) {
+    unsafe {
+      gl::DeleteTextures(1, &self.id);
+    }
+  }
+}
@@ -0,0 +1,117 @@
+extern crate image;
+use std::fmt;
+use std::fs;
+use std::io;
+use image::{ColorType, ImageBuffer, ImageError, Rgba, RgbaImage};
+
+/// Decoded texture pixels, row by row from the top, as they are handed to GL:
+/// RGBA8 when the image has an alpha channel and RGB8 otherwise, whatever the
+/// file format. Grayscale and palette images are expanded to RGB(A).
+pub struct TextureData {
+  pub width: u32,
+  pub height: u32,
+  pub has_alpha: bool,
+  // 按 sRGB 内部格式上传，采样时由 GL 转成线性值；默认关闭，和图片原样混合
+  pub srgb: bool,
+  // 颜色已乘过 alpha，混合时源因子用 ONE 而不是 SRC_ALPHA
+  pub premultiplied: bool,
+  pub data: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub enum TextureError {
+  Io(String, io::Error),
+  // 文件签名不是已知的图片格式
+  UnknownFormat(String),
+  Decode(String, ImageError),
+}
+
+impl fmt::Display for TextureError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      TextureError::Io(src, e) => write!(f, "{}: {}", src, e),
+      TextureError::UnknownFormat(src) => write!(f, "{}: not an image format we can read", src),
+      TextureError::Decode(src, e) => write!(f, "{}: failed to decode image: {}", src, e),
+    }
+  }
+}
+
+// 缺失贴图的棋盘格大小和每格的像素数
+const CHECKERBOARD_SIZE: u32 = 64;
+const CHECKER_SIZE: u32 = 8;
+
+impl TextureData {
+  pub fn load(src: &str) -> Result<TextureData, TextureError> {
+    let bytes = fs::read(src).map_err(|e| TextureError::Io(src.to_string(), e))?;
+    Self::from_bytes(&bytes, src)
+  }
+
+  /// Decodes an image file already in memory. `name` labels errors and, since TGA
+  /// has no file signature, is how a TGA file is recognised by its extension.
+  pub fn from_bytes(bytes: &[u8], name: &str) -> Result<TextureData, TextureError> {
+    // 根据文件签名获取文件格式
+    // reference: https://en.wikipedia.org/wiki/List_of_file_signatures
+    let image_format = match image::guess_format(bytes) {
+      Ok(format) => format,
+      // TGA 没有文件签名，只能看扩展名
+      Err(_) if name.to_ascii_lowercase().ends_with(".tga") => image::ImageFormat::TGA,
+      Err(_) => return Err(TextureError::UnknownFormat(name.to_string())),
+    };
+
+    let dyn_img = image::load_from_memory_with_format(bytes, image_format)
+      .map_err(|e| TextureError::Decode(name.to_string(), e))?;
+
+    // 按解码出的颜色类型判断有没有 alpha，而不是看文件格式
+    let has_alpha = match dyn_img.color() {
+      ColorType::GrayA(_) | ColorType::RGBA(_) | ColorType::BGRA(_) => true,
+      _ => false,
+    };
+    let (width, height, data) = if has_alpha {
+      let img = dyn_img.to_rgba();
+      (img.width(), img.height(), img.to_vec())
+    } else { 
+      let img = dyn_img.to_rgb();
+      (img.width(), img.height(), img.to_vec())
+    };
+    Ok(TextureData { width, height, has_alpha, srgb: false, premultiplied: false, data })
+  }
+
+  /// Multiplies the colour channels by alpha, for textures blended as premultiplied.
+  pub fn premultiply(&mut self) {
+    if !self.has_alpha || self.premultiplied {
+      return;
+    }
+    for p in self.data.chunks_mut(4) {
+      let a = p[3] as u32;
+      for c in &mut p[..3] {
+        *c = ((*c as u32 * a + 127) / 255) as u8;
+      }
+    }
+    self.premultiplied = true;
+  }
+
+  /// Magenta and black checkerboard drawn in place of a texture that failed to
+  /// load, so a bad asset stands out without stopping the game.
+  pub fn missing() -> TextureData {
+    let mut data = Vec::with_capacity((CHECKERBOARD_SIZE * CHECKERBOARD_SIZE * 3) as usize);
+    for y in 0..CHECKERBOARD_SIZE {
+      for x in 0..CHECKERBOARD_SIZE {
+        let magenta = ((x / CHECKER_SIZE) ^ (y / CHECKER_SIZE)) & 1 == 0;
+        data.extend_from_slice(if magenta { &[255, 0, 255] } else { &[0, 0, 0] });
+      }
+    }
+    TextureData { width: CHECKERBOARD_SIZE, height: CHECKERBOARD_SIZE, has_alpha: false, srgb: false, premultiplied: false, data }
+  }
+
+  /// The pixels as RGBA, opaque where the data has no alpha.
+  pub fn to_rgba(&self) -> RgbaImage {
+    if self.has_alpha {
+      return ImageBuffer::from_raw(self.width, self.height, self.data.clone()).unwrap();
+    }
+    let mut img = RgbaImage::new(self.width, self.height);
+    for (p, rgb) in img.pixels_mut().zip(self.data.chunks(3)) {
+      *p = Rgba([rgb[0], rgb[1], rgb[2], 255]);
+    }
+    img
+  }
+}
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Wrap {
+  Repeat,
+  ClampToEdge,
+  MirroredRepeat,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Filter {
+  Nearest,
+  Linear,
+}
+
+/// How a texture is uploaded and sampled. The defaults sample the way every
+/// texture did before: `REPEAT`, `LINEAR`, no anisotropic filtering. Mipmaps
+/// used to be generated too, but with a plain `LINEAR` min filter they were never
+/// read, so they are off unless asked for.
+/// Build others from there, e.g. `TextureOptions::new().filter(Filter::Nearest)`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TextureOptions {
+  pub wrap: Wrap,
+  pub min_filter: Filter,
+  pub mag_filter: Filter,
+  // 生成并在缩小时使用 mipmap
+  pub mipmaps: bool,
+  // 最大各向异性采样数，1 为关闭
+  pub anisotropy: f32,
+  pub srgb: bool,
+  pub premultiplied: bool,
+  // 打包进共用的图集；其他选项不是默认值时也不会打包
+  pub atlas: bool,
+}
+
+impl Default for TextureOptions {
+  fn default() -> TextureOptions {
+    TextureOptions {
+      wrap: Wrap::Repeat,
+      min_filter: Filter::Linear,
+      mag_filter: Filter::Linear,
+      mipmaps: false,
+      anisotropy: 1.0,
+      srgb: false,
+      premultiplied: false,
+      atlas: true,
+    }
+  }
+}
+
+impl TextureOptions {
+  pub fn new() -> TextureOptions {
+    Self::default()
+  }
+
+  pub fn wrap(mut self, wrap: Wrap) -> TextureOptions {
+    self.wrap = wrap;
+    self
+  }
+
+  /// Sets both the minifying and the magnifying filter.
+  pub fn filter(mut self, filter: Filter) -> TextureOptions {
+    self.min_filter = filter;
+    self.mag_filter = filter;
+    self
+  }
+
+  pub fn min_filter(mut self, filter: Filter) -> TextureOptions {
+    self.min_filter = filter;
+    self
+  }
+
+  pub fn mag_filter(mut self, filter: Filter) -> TextureOptions {
+    self.mag_filter = filter;
+    self
+  }
+
+  pub fn mipmaps(mut self, mipmaps: bool) -> TextureOptions {
+    self.mipmaps = mipmaps;
+    self
+  }
+
+  /// Anisotropic filtering with up to `samples` samples, clamped to what the GPU supports.
+  pub fn anisotropy(mut self, samples: f32) -> TextureOptions {
+    self.anisotropy = samples.max(1.0);
+    self
+  }
+
+  pub fn srgb(mut self, srgb: bool) -> TextureOptions {
+    self.srgb = srgb;
+    self
+  }
+
+  pub fn premultiplied(mut self, premultiplied: bool) -> TextureOptions {
+    self.premultiplied = premultiplied;
+    self
+  }
+
+  pub fn atlas(mut self, atlas: bool) -> TextureOptions {
+    self.atlas = atlas;
+    self
+  }
+}
+
+#[derive(Debug)]
+pub enum ManifestError {
+  Io(String, io::Error),
+  InvalidOption { src: String, line: usize, token: String },
+}
+
+impl fmt::Display for ManifestError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ManifestError::Io(src, e) => write!(f, "{}: {}", src, e),
+      ManifestError::InvalidOption { src, line, token } => write!(f, "{}:{}: invalid texture option `{}`", src, line, token),
+    }
+  }
+}
+
+/// Per-asset options from a texture manifest: one asset key per line followed by
+/// `name=value` options, e.g. `res/block.png filter=nearest wrap=clamp`.
+/// `#` starts a comment. Assets not listed use the defaults.
+pub fn parse_manifest(label: &str, text: &str) -> Result<HashMap<String, TextureOptions>, ManifestError> {
+  let mut manifest = HashMap::new();
+  for (line_idx, line) in text.lines().enumerate() {
+    let line = line.split('#').next().unwrap_or("");
+    let mut tokens = line.split_whitespace();
+    let key = match tokens.next() {
+      Some(key) => key,
+      None => continue,
+    };
+    let mut options = TextureOptions::new();
+    for token in tokens {
+      options = parse_option(options, token).ok_or_else(|| ManifestError::InvalidOption {
+        src: label.to_string(),
+        line: line_idx + 1,
+        token: token.to_string(),
+      })?;
+    }
+    manifest.insert(key.to_string(), options);
+  }
+  Ok(manifest)
+}
+
+fn parse_option(options: TextureOptions, token: &str) -> Option<TextureOptions> {
+  let mut parts = token.splitn(2, '=');
+  let (name, value) = (parts.next()?, parts.next()?);
+  let filter = || match value {
+    "nearest" => Some(Filter::Nearest),
+    "linear" => Some(Filter::Linear),
+    _ => None,
+  };
+  let flag = || match value {
+    "on" | "true" => Some(true),
+    "off" | "false" => Some(false),
+    _ => None,
+  };
+  Some(match name {
+    "wrap" => options.wrap(match value {
+      "repeat" => Wrap::Repeat,
+      "clamp" => Wrap::ClampToEdge,
+      "mirror" => Wrap::MirroredRepeat,
+      _ => return None,
+    }),
+    "filter" => options.filter(filter()?),
+    "min_filter" => options.min_filter(filter()?),
+    "mag_filter" => options.mag_filter(filter()?),
+    "mipmaps" => options.mipmaps(flag()?),
+    "anisotropy" => options.anisotropy(value.parse().ok().filter(|&n: &f32| n >= 1.0)?),
+    "srgb" => options.srgb(flag()?),
+    "premultiplied" => options.premultiplied(flag()?),
+    "atlas" => options.atlas(flag()?),
+    _ => return None,
+  })
+}
@@ -0,0 +1,86 @@
+/// Turns variable frame times into a whole number of fixed simulation steps.
+/// Left-over time carries into the next frame and doubles as the blend factor
+/// between the last two simulation states when rendering.
+pub struct FixedTimestep {
+  step: f32,
+  max_steps: u32,
+  accumulator: f32,
+}
+
+impl FixedTimestep {
+  /// `max_steps` caps the catch-up after a stall; time beyond it is dropped
+  /// instead of making the next frame even slower.
+  pub fn new(step: f32, max_steps: u32) -> FixedTimestep {
+    FixedTimestep {
+      step,
+      max_steps,
+      accumulator: 0.0,
+    }
+  }
+
+  pub fn step(&self) -> f32 {
+    self.step
+  }
+
+  /// Adds a frame's time and returns how many steps to simulate now.
+  pub fn advance(&mut self, frame_time: f32) -> u32 {
+    self.accumulator += frame_time.max(0.0);
+    let mut steps = (self.accumulator / self.step) as u32;
+    if steps > self.max_steps {
+      steps = self.max_steps;
+      self.accumulator = self.step * steps as f32;
+    }
+    self.accumulator -= self.step * steps as f32;
+    steps
+  }
+
+  /// How far between the previous and the current simulation state to render, in 0..1.
+  pub fn alpha(&self) -> f32 {
+    (self.accumulator / self.step).min(1.0)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn long_frame_runs_several_steps() {
+    let mut timestep = FixedTimestep::new(0.25, 8);
+    assert_eq!(timestep.advance(1.1), 4);
+    assert!((timestep.alpha() - 0.4).abs() < 1e-5);
+    // 剩下的时间留到下一帧
+    assert_eq!(timestep.advance(0.15), 1);
+  }
+
+  #[test]
+  fn short_frame_runs_no_step() {
+    let mut timestep = FixedTimestep::new(0.25, 8);
+    assert_eq!(timestep.advance(0.1), 0);
+    assert!((timestep.alpha() - 0.4).abs() < 1e-5);
+    assert_eq!(timestep.advance(0.0), 0);
+    assert_eq!(timestep.advance(-1.0), 0, "negative frame times are ignored");
+    assert!((timestep.alpha() - 0.4).abs() < 1e-5);
+  }
+
+  #[test]
+  fn stall_is_capped_and_the_backlog_dropped() {
+    let mut timestep = FixedTimestep::new(0.25, 3);
+    assert_eq!(timestep.advance(10.0), 3);
+    assert_eq!(timestep.alpha(), 0.0);
+    assert_eq!(timestep.advance(0.1), 0, "dropped time must not come back");
+  }
+
+  #[test]
+  fn alpha_stays_below_one() {
+    let mut timestep = FixedTimestep::new(1.0 / 60.0, 5);
+    let mut frame_time = 0.001;
+    for _ in 0..10_000 {
+      timestep.advance(frame_time);
+      let alpha = timestep.alpha();
+      assert!((0.0..1.0).contains(&alpha), "alpha {} after a {} s frame", alpha, frame_time);
+      // 帧时间在 1 ms 到 50 ms 之间来回变
+      frame_time = (frame_time * 1.37) % 0.05 + 0.001;
+    }
+  }
+}
@@ -1,7 +1,7 @@
 extern crate glfw;
 use self::glfw::{ Context };
 use std::sync::mpsc::Receiver;
-use crate::{WIDTH, HEIGHT};
+use breakout::{WIDTH, HEIGHT};
 
 pub struct Window {
   pub glfw: glfw::Glfw,
@@ -27,6 +27,7 @@ impl Window {
 
     win.make_current();
     win.set_key_polling(true);
+    win.set_char_polling(true);
     win.set_framebuffer_size_polling(true);
     win.set_cursor_pos_polling(true);
 
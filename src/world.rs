@@ -0,0 +1,550 @@
+use std::f32;
+use cgmath::{vec2, vec3, InnerSpace, Matrix2, Deg, Rad};
+use rand::SeedableRng;
+use rand_pcg::Pcg32;
+use crate::{WIDTH, HEIGHT};
+use crate::game_object::{GameObject, Bg, Ball, Brick, Paddle, PowerUp};
+use crate::level::{Level, MULTI_BALL_BRICK};
+use crate::campaign::Campaign;
+use crate::state::Event;
+use crate::lives::Lives;
+use crate::score::{Scoring, ScoreEvent};
+use crate::power_up::{self, PowerUpKind, ActivePowerUps};
+use crate::broadphase::Grid;
+use crate::collision::{self, Contact, ObjectId, SweepHit};
+
+/// Player input sampled for one simulation step.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Input {
+  pub left: bool,
+  pub right: bool,
+  /// Releases balls stuck to the paddle.
+  pub launch: bool,
+}
+
+/// What a sweeping ball ran into.
+#[derive(Clone, Copy)]
+enum Obstacle {
+  Wall,
+  Paddle,
+  Brick(usize),
+}
+
+/// Everything that plays the game: paddle, balls, bricks, power-ups, lives and score.
+/// No window or GL handles, so a whole game can be stepped headless.
+pub struct World {
+  pub background: Bg,
+  pub player: Paddle,
+  pub balls: Vec<Ball>,
+  pub bricks: Vec<Brick>,
+  // 未被打碎的砖块，按 bricks 中的下标索引
+  grid: Grid,
+  pub campaign: Campaign,
+  pub lives: Lives,
+  starting_lives: u32,
+  pub scoring: Scoring,
+  /// What scored during the last step, in order.
+  pub score_events: Vec<ScoreEvent>,
+  // 最近一次清空关卡的时间奖励
+  pub level_bonus: u32,
+  pub power_ups: Vec<PowerUp>,
+  pub active_power_ups: ActivePowerUps,
+  // 掉落道具用的随机数，每关用种子重新初始化
+  seed: u64,
+  rng: Pcg32,
+  // 持续累加，用于 Chaos 效果的晃动
+  pub effect_time: f32,
+}
+
+const EXTRA_LIFE_EVERY: u32 = 5000;
+const SPEED_FACTOR: f32 = 1.2;
+const PAD_SIZE_INCREASE: f32 = 50.0;
+const MULTI_BALL_COUNT: usize = 2;
+// 分裂出的新球相对原球的偏转角度
+const SPLIT_ANGLE: f32 = 25.0;
+// 一个模拟步内最多连续反弹的次数
+const MAX_BOUNCES_PER_STEP: u32 = 8;
+
+impl World {
+  pub fn new(campaign: Campaign, starting_lives: u32) -> World {
+    let (player, ball, bricks, grid) = Self::gen_level(campaign.level());
+    let seed = campaign.seed().unwrap_or_else(rand::random);
+
+    World {
+      background: Bg::new(),
+      player,
+      balls: vec![ball],
+      bricks,
+      grid,
+      campaign,
+      lives: Lives::new(starting_lives, EXTRA_LIFE_EVERY),
+      starting_lives,
+      scoring: Scoring::new(),
+      score_events: vec![],
+      level_bonus: 0,
+      power_ups: vec![],
+      active_power_ups: ActivePowerUps::new(),
+      seed,
+      rng: Pcg32::seed_from_u64(seed),
+      effect_time: 0.0,
+    }
+  }
+
+  pub fn check_collision(goa: &GameObject, gob: &GameObject) -> bool {
+    let collision_x = !(
+      goa.position[0] >= gob.position[0] + gob.size[0]
+      || goa.position[0] + goa.size[0] < gob.position[0]
+    );
+    let collision_y = !(
+      goa.position[1] >= gob.position[1] + gob.size[1]
+      || goa.position[1] + goa.size[1] < gob.position[1]
+    );
+    collision_x && collision_y
+  }
+
+  fn gen_level(level: &Level) -> (Paddle, Ball, Vec<Brick>, Grid) {
+    let bricks = level.bricks(WIDTH as f32, HEIGHT as f32 / 2.0);
+    // 网格格子和砖块一样大，覆盖整个窗口
+    let mut grid = Grid::new(vec2(WIDTH as f32, HEIGHT as f32), level.brick_size(WIDTH as f32, HEIGHT as f32 / 2.0));
+    for (idx, brick) in bricks.iter().enumerate() {
+      let brick_go = &brick.game_object;
+      grid.insert(idx, brick_go.position, brick_go.position + brick_go.size);
+    }
+
+    let player = Paddle::new(vec2(500.0, 580.0));
+    let ball = Self::ball_on_paddle(&player);
+
+    (player, ball, bricks, grid)
+  }
+
+  /// A new ball stuck to the middle of the paddle's top, ready to be launched.
+  fn ball_on_paddle(player: &Paddle) -> Ball {
+    let player_go = &player.game_object;
+    let radius = Ball::new(vec2(0.0, 0.0)).radius;
+    Ball::new(vec2(
+      player_go.position.x + player_go.size.x / 2.0 - radius,
+      player_go.position.y - 2.0 * radius
+    ))
+  }
+}
+
+impl World {
+  fn check_ball_player_collision(&mut self) {
+    let player_go = &self.player.game_object;
+    let sticky = self.active_power_ups.is_active(PowerUpKind::Sticky);
+    for (ball_idx, ball) in self.balls.iter_mut().enumerate() {
+      let center = ball.game_object.position + ball.game_object.size / 2.0;
+      let contact = collision::circle_aabb_contact(
+        ObjectId::Ball(ball_idx), center, ball.radius,
+        ObjectId::Paddle, player_go.position, player_go.position + player_go.size
+      );
+      let contact = match contact {
+        Some(contact) => contact,
+        None => continue,
+      };
+      // 打在挡板侧面或底部时沿真实法线反弹
+      if contact.normal.y >= 0.0 {
+        ball.velocity = collision::bounce(ball.velocity, contact.normal);
+        ball.game_object.position += contact.normal * contact.penetration;
+        continue;
+      }
+      if !ball.is_stuck {
+        self.scoring.paddle_hit();
+        ball.is_stuck = sticky;
+      }
+      ball.game_object.position.y = player_go.position.y - ball.game_object.size.y;
+      Self::bounce_off_paddle(ball, player_go);
+    }
+  }
+
+  /// Sends the ball back up, angled by how far from the paddle's centre it landed.
+  fn bounce_off_paddle(ball: &mut Ball, player_go: &GameObject) {
+    let ball_go = &ball.game_object;
+    ball.velocity.y = -ball.velocity.y;
+    // 球碰撞点距离挡板中心的距离
+    let percentage = (ball_go.position.x + ball_go.size.x / 2.0 - player_go.position.x - player_go.size.x / 2.0) / (player_go.size.x / 2.0);
+    let angle = ball.velocity.angle(vec2(0.0, -1.0));
+    let mut next_angle = angle + Rad::from(Deg(20.0 * percentage));
+    if next_angle > Rad::from(Deg(45.0)) {
+      next_angle = Rad::from(Deg(45.0));
+    } else if next_angle < Rad::from(Deg(-45.0)) {
+      next_angle = Rad::from(Deg(-45.0));
+    }
+    let rotation = Matrix2::from_angle(next_angle);
+    ball.velocity = rotation * vec2(0.0, -1.0) * ball.velocity.magnitude();
+  }
+
+  /// Brick `brick_idx` was hit by ball `ball_idx`: destroys it if it's breakable, with
+  /// score, power-up drop and ball split. Returns whether the ball should bounce off it.
+  fn hit_brick(&mut self, ball_idx: usize, brick_idx: usize, events: &mut Vec<ScoreEvent>) -> bool {
+    let brick = &mut self.bricks[brick_idx];
+    if brick.is_solid {
+      return true;
+    }
+    brick.is_destroyed = true;
+    self.grid.remove(brick_idx);
+    let (kind, position) = (brick.kind, brick.game_object.position);
+    events.push(self.scoring.brick_destroyed(kind));
+    // 多球砖块被打碎时，从击中它的球分裂出新球
+    if kind == MULTI_BALL_BRICK {
+      self.split_ball(ball_idx, 1);
+    }
+    if let Some(power_up) = power_up::roll_drop(kind, &mut self.rng) {
+      self.power_ups.push(PowerUp::new(power_up, position));
+    }
+    // 穿透效果下不反弹
+    !self.active_power_ups.is_active(PowerUpKind::PassThrough)
+  }
+
+  /// Bounces balls off the bricks they overlap, returning the score for each brick destroyed.
+  /// All of a ball's contacts are gathered first and resolved together, so a ball
+  /// touching several bricks at once bounces exactly once.
+  fn check_ball_brick_collision(&mut self) -> Vec<ScoreEvent> {
+    let mut events = vec![];
+    for ball_idx in 0..self.balls.len() {
+      let (center, radius) = {
+        let ball = &self.balls[ball_idx];
+        (ball.game_object.position + ball.game_object.size / 2.0, ball.radius)
+      };
+      let mut contacts = vec![];
+      let reach = vec2(radius, radius);
+      for brick_idx in self.grid.query(center - reach, center + reach) {
+        let brick = &self.bricks[brick_idx];
+        let brick_go = &brick.game_object;
+        let contact = collision::circle_aabb_contact(
+          ObjectId::Ball(ball_idx), center, radius,
+          ObjectId::Brick(brick.id()), brick_go.position, brick_go.position + brick_go.size
+        );
+        let contact = match contact {
+          Some(contact) => contact,
+          None => continue,
+        };
+        if self.hit_brick(ball_idx, brick_idx, &mut events) {
+          contacts.push(contact);
+        }
+      }
+      if let Some((normal, penetration)) = collision::combine_contacts(&contacts) {
+        let ball = &mut self.balls[ball_idx];
+        ball.velocity = collision::bounce(ball.velocity, normal);
+        ball.game_object.position += normal * penetration;
+      }
+    }
+    events
+  }
+
+  /// Moves ball `ball_idx` along its velocity for `dt` seconds. The ball stops at the
+  /// earliest wall, paddle or brick it would touch, bounces and travels the rest of
+  /// the step from there, so a fast ball can't skip through anything.
+  fn move_ball(&mut self, ball_idx: usize, dt: f32, events: &mut Vec<ScoreEvent>) {
+    let mut remaining = dt;
+    for _ in 0..MAX_BOUNCES_PER_STEP {
+      let ball = &self.balls[ball_idx];
+      if ball.is_stuck || remaining <= 0.0 {
+        return;
+      }
+      let center = ball.game_object.position + ball.game_object.size / 2.0;
+      let motion = ball.velocity * remaining;
+
+      let mut earliest = collision::sweep_circle_walls(center, ball.radius, motion, WIDTH as f32)
+        .map(|hit| (hit, Obstacle::Wall));
+      let mut consider = |hit: Option<SweepHit>, obstacle: Obstacle| {
+        if let Some(hit) = hit {
+          if earliest.map_or(true, |(e, _)| hit.time < e.time) {
+            earliest = Some((hit, obstacle));
+          }
+        }
+      };
+      let player_go = &self.player.game_object;
+      consider(
+        collision::sweep_circle_aabb(center, ball.radius, motion, player_go.position, player_go.position + player_go.size),
+        Obstacle::Paddle
+      );
+      let end = center + motion;
+      let reach = vec2(ball.radius, ball.radius);
+      let (min, max) = (vec2(center.x.min(end.x), center.y.min(end.y)) - reach, vec2(center.x.max(end.x), center.y.max(end.y)) + reach);
+      for brick_idx in self.grid.query(min, max) {
+        let brick_go = &self.bricks[brick_idx].game_object;
+        consider(
+          collision::sweep_circle_aabb(center, ball.radius, motion, brick_go.position, brick_go.position + brick_go.size),
+          Obstacle::Brick(brick_idx)
+        );
+      }
+
+      let (hit, obstacle) = match earliest {
+        Some(earliest) => earliest,
+        None => {
+          self.balls[ball_idx].game_object.position += motion;
+          return;
+        },
+      };
+      self.balls[ball_idx].game_object.position += motion * hit.time;
+      remaining -= remaining * hit.time;
+      match obstacle {
+        Obstacle::Wall => {
+          let ball = &mut self.balls[ball_idx];
+          ball.velocity = collision::reflect(ball.velocity, hit.normal);
+        },
+        // 打在挡板上表面按挡板规则反弹，打在侧面则直接反射
+        Obstacle::Paddle if hit.normal.y < 0.0 => {
+          self.scoring.paddle_hit();
+          let sticky = self.active_power_ups.is_active(PowerUpKind::Sticky);
+          let ball = &mut self.balls[ball_idx];
+          Self::bounce_off_paddle(ball, &self.player.game_object);
+          ball.is_stuck = sticky;
+        },
+        Obstacle::Paddle => {
+          let ball = &mut self.balls[ball_idx];
+          ball.velocity = collision::reflect(ball.velocity, hit.normal);
+        },
+        Obstacle::Brick(brick_idx) => {
+          if self.hit_brick(ball_idx, brick_idx, events) {
+            let ball = &mut self.balls[ball_idx];
+            ball.velocity = collision::reflect(ball.velocity, hit.normal);
+          }
+        },
+      }
+    }
+  }
+
+  fn check_ball_border_collision(&mut self) {
+    for ball in self.balls.iter_mut() {
+      let ball_go = &mut ball.game_object;
+
+      if ball_go.position.x >= WIDTH as f32 - ball_go.size.x {
+          ball.velocity.x = -ball.velocity.x;
+          ball_go.position.x = WIDTH as f32 - ball_go.size.x;
+      } else if ball_go.position[0] <= 0.0 {
+        ball.velocity.x = -ball.velocity.x;
+        ball_go.position.x = 0.0;
+      }
+      if ball_go.position.y <= 0.0 {
+        ball.velocity.y = -ball.velocity.y;
+        ball_go.position.y = 0.0;
+      }
+    }
+  }
+
+  /// Bounces balls off each other as equal-mass circles.
+  fn check_ball_ball_collision(&mut self) {
+    for i in 0..self.balls.len() {
+      let (head, tail) = self.balls.split_at_mut(i + 1);
+      let a = &mut head[i];
+      for (j, b) in tail.iter_mut().enumerate() {
+        if a.is_stuck || b.is_stuck {
+          continue;
+        }
+        let contact = collision::circle_circle_contact(
+          ObjectId::Ball(i), a.game_object.position + a.game_object.size / 2.0, a.radius,
+          ObjectId::Ball(i + 1 + j), b.game_object.position + b.game_object.size / 2.0, b.radius
+        );
+        let Contact { normal, penetration, .. } = match contact {
+          Some(contact) => contact,
+          None => continue,
+        };
+        // 沿法线方向交换速度分量，只处理相互靠近的情况（法线由 b 指向 a）
+        let approach = (b.velocity - a.velocity).dot(normal);
+        if approach > 0.0 {
+          a.velocity += normal * approach;
+          b.velocity -= normal * approach;
+        }
+        a.game_object.position += normal * (penetration / 2.0);
+        b.game_object.position -= normal * (penetration / 2.0);
+      }
+    }
+  }
+
+  /// Adds `count` balls flying off from ball `ball_idx` at spread out angles.
+  fn split_ball(&mut self, ball_idx: usize, count: usize) {
+    let (position, velocity) = {
+      let source = &self.balls[ball_idx];
+      (source.game_object.position, source.velocity)
+    };
+    let color = self.balls[ball_idx].game_object.color;
+    for n in 0..count {
+      let sign = if n % 2 == 0 { 1.0 } else { -1.0 };
+      let angle = Deg(SPLIT_ANGLE * sign * (n / 2 + 1) as f32);
+      let mut ball = Ball::new(position);
+      ball.is_stuck = false;
+      ball.velocity = Matrix2::from_angle(angle) * velocity;
+      ball.game_object.color = color;
+      self.balls.push(ball);
+    }
+  }
+
+  fn update_power_ups(&mut self, dt: f32) {
+    for power_up in self.power_ups.iter_mut() {
+      power_up.game_object.position += power_up.velocity * dt;
+    }
+    let mut caught = vec![];
+    let player_go = &self.player.game_object;
+    self.power_ups.retain(|power_up| {
+      if Self::check_collision(player_go, &power_up.game_object) {
+        caught.push(power_up.kind);
+        return false;
+      }
+      power_up.game_object.position.y < HEIGHT as f32
+    });
+    for kind in caught {
+      // 瞬时道具没有计时器
+      if kind.duration() == 0.0 || self.active_power_ups.activate(kind) {
+        self.apply_power_up(kind);
+      }
+    }
+    for kind in self.active_power_ups.tick(dt) {
+      self.revert_power_up(kind);
+    }
+  }
+
+  fn apply_power_up(&mut self, kind: PowerUpKind) {
+    match kind {
+      PowerUpKind::Speed => self.balls.iter_mut().for_each(|ball| ball.velocity *= SPEED_FACTOR),
+      PowerUpKind::Sticky => self.player.game_object.color = kind.color(),
+      PowerUpKind::PassThrough => self.balls.iter_mut().for_each(|ball| ball.game_object.color = kind.color()),
+      PowerUpKind::PadSizeIncrease => {
+        // 变长后不能超出右边界
+        let player_go = &mut self.player.game_object;
+        player_go.size.x += PAD_SIZE_INCREASE;
+        player_go.position.x = player_go.position.x.min(WIDTH as f32 - player_go.size.x);
+      },
+      PowerUpKind::MultiBall => {
+        // 从第一个在飞行中的球分裂，没有则从粘在挡板上的球分裂
+        let source = self.balls.iter().position(|ball| !ball.is_stuck).unwrap_or(0);
+        if !self.balls.is_empty() {
+          self.split_ball(source, MULTI_BALL_COUNT);
+        }
+      },
+      PowerUpKind::Confuse | PowerUpKind::Chaos => {},
+    }
+  }
+
+  fn revert_power_up(&mut self, kind: PowerUpKind) {
+    let white = vec3(1.0, 1.0, 1.0);
+    match kind {
+      PowerUpKind::Speed => self.balls.iter_mut().for_each(|ball| ball.velocity /= SPEED_FACTOR),
+      PowerUpKind::Sticky => self.player.game_object.color = white,
+      PowerUpKind::PassThrough => self.balls.iter_mut().for_each(|ball| ball.game_object.color = white),
+      PowerUpKind::PadSizeIncrease => {
+        let player_go = &mut self.player.game_object;
+        player_go.size.x -= PAD_SIZE_INCREASE;
+        player_go.position.x = player_go.position.x.min(WIDTH as f32 - player_go.size.x);
+      },
+      PowerUpKind::MultiBall | PowerUpKind::Confuse | PowerUpKind::Chaos => {},
+    }
+  }
+
+  fn clear_power_ups(&mut self) {
+    self.power_ups.clear();
+    for kind in self.active_power_ups.clear() {
+      self.revert_power_up(kind);
+    }
+  }
+
+  /// Drops balls that left the screen; a life is only lost with the last one.
+  fn check_ball_lost(&mut self) -> Option<Event> {
+    self.balls.retain(|ball| ball.game_object.position.y <= HEIGHT as f32);
+    if !self.balls.is_empty() {
+      return None;
+    }
+    self.clear_power_ups();
+    if self.lives.lose() {
+      // 保留砖块，新球粘在挡板上
+      self.balls.push(Self::ball_on_paddle(&self.player));
+      None
+    } else {
+      Some(Event::OutOfLives)
+    }
+  }
+
+  fn check_level_cleared(&mut self) -> Option<Event> {
+    if !Campaign::is_level_cleared(&self.bricks) {
+      return None;
+    }
+    let bonus = self.scoring.level_cleared();
+    self.level_bonus = bonus.map_or(0, |bonus| bonus.total());
+    self.score_events.extend(bonus);
+    self.lives.award_for_score(self.scoring.score());
+    Some(if self.campaign.advance() { Event::LevelCleared } else { Event::CampaignCleared })
+  }
+
+  /// Starts over with full lives and no score.
+  pub fn new_run(&mut self) {
+    self.lives = Lives::new(self.starting_lives, EXTRA_LIFE_EVERY);
+    self.scoring = Scoring::new();
+  }
+
+  /// 重新开始当前关卡
+  pub fn reset(&mut self) {
+    let (player, ball, bricks, grid) = Self::gen_level(self.campaign.level());
+    self.player = player;
+    self.balls = vec![ball];
+    self.bricks = bricks;
+    self.grid = grid;
+    self.power_ups.clear();
+    self.active_power_ups.clear();
+    self.rng = Pcg32::seed_from_u64(self.seed.wrapping_add(self.campaign.current() as u64));
+    self.scoring.start_level();
+    self.score_events.clear();
+  }
+
+  pub fn save_previous_positions(&mut self) {
+    let player_go = &mut self.player.game_object;
+    player_go.previous_position = player_go.position;
+    for ball in self.balls.iter_mut() {
+      ball.game_object.previous_position = ball.game_object.position;
+    }
+    for power_up in self.power_ups.iter_mut() {
+      power_up.game_object.previous_position = power_up.game_object.position;
+    }
+  }
+
+  /// Runs one step of play of `dt` seconds. Returns the outcome if the step ended the
+  /// level or the run; what scored is left in `score_events`.
+  pub fn step(&mut self, dt: f32, input: Input) -> Option<Event> {
+    self.scoring.tick(dt);
+    self.effect_time += dt;
+    self.check_ball_border_collision();
+    self.check_ball_player_collision();
+    self.check_ball_ball_collision();
+    let mut events = self.check_ball_brick_collision();
+
+    let player = &mut self.player;
+    let player_go = &mut player.game_object;
+
+    // Confuse 效果：左右操作反转
+    let (left, right) = if self.active_power_ups.is_active(PowerUpKind::Confuse) {
+      (input.right, input.left)
+    } else {
+      (input.left, input.right)
+    };
+
+    if left && player_go.position.x > 0.0 {
+      player_go.position.x -= player.velocity * dt;
+    }
+
+    if right && player_go.position.x < WIDTH as f32 - player_go.size.x {
+      player_go.position.x += player.velocity * dt;
+    }
+
+    for ball in self.balls.iter_mut() {
+      let ball_go = &mut ball.game_object;
+      if input.launch {
+        ball.is_stuck = false;
+      }
+
+      if ball.is_stuck {
+        ball_go.position.x = player_go.position.x + player_go.size.x / 2.0 - ball_go.size.x / 2.0;
+        ball_go.position.y = player_go.position.y - ball_go.size.y;
+      }
+    }
+    for ball_idx in 0..self.balls.len() {
+      self.move_ball(ball_idx, dt, &mut events);
+    }
+    if !events.is_empty() {
+      self.lives.award_for_score(self.scoring.score());
+    }
+    self.score_events = events;
+
+    self.update_power_ups(dt);
+    self.check_ball_lost().or_else(|| self.check_level_cleared())
+  }
+}
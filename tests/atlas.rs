@@ -0,0 +1,75 @@
+//! Packs images into an atlas and checks the regions and padding.
+
+use cgmath::vec2;
+use image::{Rgba, RgbaImage};
+use breakout::assets::Assets;
+use breakout::atlas::{Atlas, Region};
+use breakout::game_object;
+use breakout::text;
+
+fn solid(width: u32, height: u32, shade: u8) -> RgbaImage {
+  RgbaImage::from_pixel(width, height, Rgba([shade, shade, shade, 255]))
+}
+
+#[test]
+fn regions_hold_their_images_without_overlapping() {
+  let sizes = [(30, 10), (7, 7), (64, 20), (1, 1), (16, 33), (50, 5)];
+  let images = sizes.iter().enumerate()
+    .map(|(idx, &(w, h))| (format!("image{}", idx), solid(w, h, idx as u8 * 40)))
+    .collect();
+  let atlas = Atlas::pack(images);
+
+  let regions: Vec<Region> = (0..sizes.len()).map(|idx| atlas.region(&format!("image{}", idx)).unwrap()).collect();
+  for (idx, (region, &(w, h))) in regions.iter().zip(sizes.iter()).enumerate() {
+    assert_eq!((region.width, region.height), (w, h));
+    assert!(region.x >= 1 && region.y >= 1);
+    assert!(region.x + w < atlas.image().width() && region.y + h < atlas.image().height());
+    for y in region.y..region.y + h {
+      for x in region.x..region.x + w {
+        assert_eq!(atlas.image().get_pixel(x, y)[0], idx as u8 * 40, "image{} at {},{}", idx, x, y);
+      }
+    }
+    // 区域加上一圈填充也不能和别的区域重叠
+    for other in &regions[idx + 1..] {
+      let apart = region.x + w + 2 <= other.x || other.x + other.width + 2 <= region.x
+        || region.y + h + 2 <= other.y || other.y + other.height + 2 <= region.y;
+      assert!(apart, "{:?} overlaps {:?}", region, other);
+    }
+  }
+  assert!(atlas.region("missing").is_none());
+}
+
+#[test]
+fn padding_repeats_the_edge_pixels() {
+  let mut img = solid(2, 2, 0);
+  img.put_pixel(0, 0, Rgba([255, 0, 0, 255]));
+  img.put_pixel(1, 1, Rgba([0, 0, 255, 255]));
+  let atlas = Atlas::pack(vec![(String::from("img"), img)]);
+  let region = atlas.region("img").unwrap();
+  let pixel = |dx: i32, dy: i32| *atlas.image().get_pixel((region.x as i32 + dx) as u32, (region.y as i32 + dy) as u32);
+  assert_eq!(pixel(-1, -1), Rgba([255, 0, 0, 255]));
+  assert_eq!(pixel(0, -1), Rgba([255, 0, 0, 255]));
+  assert_eq!(pixel(2, 2), Rgba([0, 0, 255, 255]));
+  assert_eq!(pixel(2, 1), Rgba([0, 0, 255, 255]));
+}
+
+#[test]
+fn uv_covers_the_region() {
+  let atlas = Atlas::pack(vec![(String::from("a"), solid(10, 20, 0)), (String::from("b"), solid(4, 4, 0))]);
+  let (width, height) = (atlas.image().width() as f32, atlas.image().height() as f32);
+  let region = atlas.region("b").unwrap();
+  let uv = atlas.uv("b").unwrap();
+  assert_eq!(uv.min, vec2(region.x as f32 / width, region.y as f32 / height));
+  assert_eq!(uv.max, vec2((region.x + 4) as f32 / width, (region.y + 4) as f32 / height));
+  assert_eq!(uv.lerp(vec2(0.5, 0.5)), (uv.min + uv.max) / 2.0);
+}
+
+#[test]
+fn game_textures_share_one_atlas() {
+  let atlas = Atlas::from_assets(&Assets::default());
+  assert!(atlas.region(text::TEXTURE).is_some());
+  for &src in game_object::TEXTURES.iter() {
+    // 背景在清单里标了 atlas=off
+    assert_eq!(atlas.region(src).is_some(), src != "res/bg.jpg", "{}", src);
+  }
+}
@@ -0,0 +1,166 @@
+//! Collision queries on their own and inside `World`: fast balls must never
+//! tunnel through thin bricks or the paddle, and a ball touching several bricks
+//! at once bounces exactly once.
+
+mod common;
+
+use cgmath::{vec2, InnerSpace, Vector2};
+use breakout::{WIDTH, HEIGHT};
+use breakout::collision::{self, SweepHit};
+use breakout::world::{Input, World};
+use common::{world, DT};
+
+const RADIUS: f32 = 12.5;
+const SPEEDS: [f32; 4] = [1e3, 1e4, 1e5, 1e6];
+// 一排砖块，只有一块砖那么厚
+const ROW_TOP: f32 = 250.0;
+const ROW_BOTTOM: f32 = 270.0;
+const BRICK_WIDTH: f32 = 800.0 / 12.0;
+// 球离接触点的距离，最慢的速度一步也能走到
+const GAP: f32 = 10.0;
+
+/// Earliest hit of the sweep against a row of twelve bricks.
+fn sweep_row(center: Vector2<f32>, motion: Vector2<f32>) -> Option<SweepHit> {
+  (0..12)
+    .filter_map(|column| {
+      let min = vec2(column as f32 * BRICK_WIDTH, ROW_TOP);
+      collision::sweep_circle_aabb(center, RADIUS, motion, min, min + vec2(BRICK_WIDTH, ROW_BOTTOM - ROW_TOP))
+    })
+    .fold(None, |earliest: Option<SweepHit>, hit| match earliest {
+      Some(e) if e.time <= hit.time => Some(e),
+      _ => Some(hit),
+    })
+}
+
+fn assert_close(actual: f32, expected: f32, what: &str) {
+  assert!((actual - expected).abs() < 0.5, "{}: {} is not {}", what, actual, expected);
+}
+
+// x = 400 正好是两块砖的接缝，法线可能来自圆角，允许一点误差
+fn assert_normal(actual: Vector2<f32>, expected: Vector2<f32>, what: &str) {
+  assert!(actual.dot(expected) > 0.999, "{}: normal {:?} is not {:?}", what, actual, expected);
+}
+
+#[test]
+fn fast_ball_is_stopped_by_a_one_brick_thick_row() {
+  for &speed in &SPEEDS {
+    for &direction in &[vec2(0.0, -1.0), vec2(0.3, -1.0), vec2(-0.7, -1.0)] {
+      let center = vec2(400.0, ROW_BOTTOM + RADIUS + GAP);
+      let motion = direction.normalize() * speed * DT;
+      let hit = sweep_row(center, motion).unwrap_or_else(|| panic!("tunnelled at {} px/s", speed));
+      assert_normal(hit.normal, vec2(0.0, 1.0), &format!("{} px/s", speed));
+      assert_close(center.y + motion.y * hit.time, ROW_BOTTOM + RADIUS, "centre at contact");
+      assert!(collision::reflect(direction, hit.normal).y > 0.0);
+    }
+  }
+}
+
+#[test]
+fn fast_ball_is_stopped_by_the_top_of_the_row() {
+  for &speed in &SPEEDS {
+    let center = vec2(123.0, ROW_TOP - RADIUS - GAP);
+    let motion = vec2(0.2, 1.0).normalize() * speed * DT;
+    let hit = sweep_row(center, motion).unwrap_or_else(|| panic!("tunnelled at {} px/s", speed));
+    assert_normal(hit.normal, vec2(0.0, -1.0), &format!("{} px/s", speed));
+    assert_close(center.y + motion.y * hit.time, ROW_TOP - RADIUS, "centre at contact");
+  }
+}
+
+#[test]
+fn fast_ball_is_stopped_by_the_paddle() {
+  let (min, max) = (vec2(350.0, 580.0), vec2(450.0, 600.0));
+  for &speed in &SPEEDS {
+    let center = vec2(380.0, min.y - RADIUS - GAP);
+    let motion = vec2(0.05, 1.0).normalize() * speed * DT;
+    let hit = collision::sweep_circle_aabb(center, RADIUS, motion, min, max)
+      .unwrap_or_else(|| panic!("tunnelled at {} px/s", speed));
+    assert_eq!(hit.normal, vec2(0.0, -1.0), "at {} px/s", speed);
+    assert_close(center.y + motion.y * hit.time, min.y - RADIUS, "centre at contact");
+  }
+}
+
+#[test]
+fn fast_ball_is_stopped_by_the_walls() {
+  for &speed in &SPEEDS {
+    let cases = [
+      (vec2(RADIUS + GAP, 300.0), vec2(-1.0, 0.0), vec2(1.0, 0.0)),
+      (vec2(WIDTH as f32 - RADIUS - GAP, 300.0), vec2(1.0, 0.0), vec2(-1.0, 0.0)),
+      (vec2(400.0, RADIUS + GAP), vec2(0.0, -1.0), vec2(0.0, 1.0)),
+    ];
+    for &(center, direction, normal) in &cases {
+      let motion = direction * speed * DT;
+      let hit = collision::sweep_circle_walls(center, RADIUS, motion, WIDTH as f32)
+        .unwrap_or_else(|| panic!("left the playfield at {} px/s", speed));
+      assert_eq!(hit.normal, normal);
+      let contact = center + motion * hit.time;
+      assert!(contact.x >= RADIUS - 0.5 && contact.x <= WIDTH as f32 - RADIUS + 0.5 && contact.y >= RADIUS - 0.5);
+    }
+  }
+}
+
+#[test]
+fn world_reflects_fast_balls_off_bricks_and_paddle() {
+  for &speed in &SPEEDS {
+    let mut world = world();
+    let bricks = world.bricks.iter().filter(|brick| !brick.is_destroyed).count();
+    let (position, size) = (world.player.game_object.position, world.player.game_object.size);
+    let ball = &mut world.balls[0];
+    ball.is_stuck = false;
+    // 球在最下面一排砖块正下方
+    ball.game_object.position = vec2(position.x + size.x / 2.0 - RADIUS, HEIGHT as f32 / 2.0 + GAP);
+    ball.velocity = vec2(0.0, -speed);
+
+    world.step(DT, Input::default());
+    assert!(world.bricks.iter().filter(|brick| !brick.is_destroyed).count() < bricks, "no brick hit at {} px/s", speed);
+    for _ in 0..60 {
+      world.step(DT, Input::default());
+      for ball in &world.balls {
+        let ball_go = &ball.game_object;
+        assert!(ball_go.position.x >= -0.5 && ball_go.position.x + ball_go.size.x <= WIDTH as f32 + 0.5, "at {} px/s", speed);
+        assert!(ball_go.position.y >= -0.5 && ball_go.position.y <= HEIGHT as f32, "at {} px/s", speed);
+      }
+    }
+    assert_eq!(world.lives.remaining(), 3, "ball went through the paddle at {} px/s", speed);
+  }
+}
+
+/// Puts the only ball just inside the bottom of brick `idx`, moving up: under its
+/// middle, or under the seam with the next brick when `at_seam`.
+fn ball_under_brick(world: &mut World, idx: usize, at_seam: bool) {
+  let (position, size) = (world.bricks[idx].game_object.position, world.bricks[idx].game_object.size);
+  let x = if at_seam { position.x + size.x } else { position.x + size.x / 2.0 };
+  let ball = &mut world.balls[0];
+  ball.is_stuck = false;
+  ball.game_object.position = vec2(x - RADIUS, position.y + size.y - 2.0);
+  ball.velocity = vec2(0.0, -500.0);
+}
+
+#[test]
+fn world_breaks_both_bricks_at_a_seam_and_bounces_once() {
+  let mut world = world();
+  // 最下面一排的前两块砖都设成可打碎的
+  let idx = world.bricks.len() - 12;
+  for brick in &mut world.bricks[idx..idx + 2] {
+    brick.is_solid = false;
+  }
+  ball_under_brick(&mut world, idx, true);
+
+  world.step(1.0 / 120.0, Input::default());
+  assert!(world.bricks[idx].is_destroyed && world.bricks[idx + 1].is_destroyed);
+  assert_eq!(world.score_events.len(), 2);
+  assert!(world.balls[0].velocity.y > 0.0, "velocity flipped twice: {:?}", world.balls[0].velocity);
+}
+
+#[test]
+fn world_bounces_off_solid_bricks_without_breaking_them() {
+  let mut world = world();
+  let idx = world.bricks.len() - 1;
+  world.bricks[idx].is_solid = true;
+  ball_under_brick(&mut world, idx, false);
+
+  world.step(1.0 / 120.0, Input::default());
+  assert!(!world.bricks[idx].is_destroyed);
+  assert!(world.score_events.is_empty());
+  assert!(world.balls[0].velocity.y > 0.0);
+  assert!(world.balls[0].game_object.position.y >= world.bricks[idx].game_object.position.y + world.bricks[idx].game_object.size.y - 0.5);
+}
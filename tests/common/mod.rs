@@ -0,0 +1,17 @@
+//! Setup shared by the integration tests. Each test crate uses only part of it.
+#![allow(dead_code)]
+
+use breakout::campaign::Campaign;
+use breakout::world::World;
+
+/// One simulation step at 60 Hz.
+pub const DT: f32 = 1.0 / 60.0;
+
+/// Two random 12×6 levels from a fixed seed, with three lives.
+pub fn world() -> World {
+  world_with_lives(3)
+}
+
+pub fn world_with_lives(lives: u32) -> World {
+  World::new(Campaign::random(2, 12, 6, 42), lives)
+}
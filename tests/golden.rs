@@ -0,0 +1,125 @@
+//! Renders fixed scenes with the software renderer and compares them with the
+//! reference images in `tests/golden/`. A mismatch writes the rendered frame and
+//! a diff image to `target/golden/`. After an intended change to rendering,
+//! regenerate the references with `UPDATE_GOLDEN=1 cargo test --test golden`
+//! and check the new images before committing them.
+
+mod common;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use cgmath::{vec2, vec3, Vector2};
+use image::{Rgba, RgbaImage};
+use breakout::{WIDTH, HEIGHT};
+use breakout::game_object::{Ball, GameObject};
+use breakout::highscore::HighScores;
+use breakout::power_up::PowerUpKind;
+use breakout::renderer::Renderer;
+use breakout::screen::Screen;
+use breakout::software_renderer::SoftwareRenderer;
+use breakout::state::State;
+use breakout::world::World;
+use common::world;
+
+// 每个通道允许的误差，吸收不同平台上浮点运算的细微差别
+const TOLERANCE: u8 = 2;
+
+fn place(game_object: &mut GameObject, position: Vector2<f32>) {
+  game_object.position = position;
+  game_object.previous_position = position;
+}
+
+fn render(state: State, world: &World) -> RgbaImage {
+  let high_scores = HighScores::load(None);
+  let mut renderer = SoftwareRenderer::new(WIDTH, HEIGHT);
+  renderer.clear(vec3(0.2, 0.3, 0.3));
+  Screen { state, world, high_scores: &high_scores, name_entry: None }.draw(&mut renderer, 1.0);
+  renderer.present();
+  renderer.image().clone()
+}
+
+fn check(name: &str, actual: &RgbaImage) {
+  let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+  let reference_path = dir.join("tests").join("golden").join(format!("{}.png", name));
+  if env::var_os("UPDATE_GOLDEN").is_some() {
+    actual.save(&reference_path).unwrap();
+    return;
+  }
+  let reference = image::open(&reference_path)
+    .unwrap_or_else(|e| panic!("{}: {} (run with UPDATE_GOLDEN=1 to create it)", reference_path.display(), e))
+    .to_rgba();
+  assert_eq!(reference.dimensions(), actual.dimensions(), "{}: frame size changed", name);
+
+  // 超出误差的像素在差异图中标红，其余像素变暗作为参照
+  let mut diff = RgbaImage::new(actual.width(), actual.height());
+  let mut mismatches = 0;
+  for (x, y, expected) in reference.enumerate_pixels() {
+    let got = actual.get_pixel(x, y);
+    let off = expected.0.iter().zip(got.0.iter()).any(|(&e, &g)| (e as i16 - g as i16).abs() > TOLERANCE as i16);
+    diff.put_pixel(x, y, if off {
+      mismatches += 1;
+      Rgba([255, 0, 0, 255])
+    } else {
+      Rgba([got[0] / 4, got[1] / 4, got[2] / 4, 255])
+    });
+  }
+  if mismatches == 0 {
+    return;
+  }
+  let out = dir.join("target").join("golden");
+  fs::create_dir_all(&out).unwrap();
+  actual.save(out.join(format!("{}.actual.png", name))).unwrap();
+  diff.save(out.join(format!("{}.diff.png", name))).unwrap();
+  panic!("{}: {} pixels differ from {}, see {}", name, mismatches, reference_path.display(), out.display());
+}
+
+#[test]
+fn seeded_level() {
+  check("seeded_level", &render(State::Playing, &world()));
+}
+
+#[test]
+fn balls_at_known_positions() {
+  let mut world = world();
+  world.balls = [vec2(0.0, 320.0), vec2(387.5, 420.0), vec2(775.0, 320.0), vec2(200.3, 500.7)]
+    .iter()
+    .map(|&position| {
+      let mut ball = Ball::new(position);
+      place(&mut ball.game_object, position);
+      ball
+    })
+    .collect();
+  check("balls_at_known_positions", &render(State::Playing, &world));
+}
+
+#[test]
+fn paddle_at_left_edge() {
+  let mut world = world();
+  let y = world.player.game_object.position.y;
+  place(&mut world.player.game_object, vec2(0.0, y));
+  check("paddle_at_left_edge", &render(State::Playing, &world));
+}
+
+#[test]
+fn paddle_at_right_edge() {
+  let mut world = world();
+  let go = &mut world.player.game_object;
+  let position = vec2(WIDTH as f32 - go.size.x, go.position.y);
+  place(go, position);
+  check("paddle_at_right_edge", &render(State::Playing, &world));
+}
+
+// Chaos 效果会旋转所有物体，覆盖模型矩阵中的旋转部分
+#[test]
+fn rotated_sprites() {
+  let mut world = world();
+  world.active_power_ups.activate(PowerUpKind::Chaos);
+  world.effect_time = 0.1;
+  check("rotated_sprites", &render(State::Playing, &world));
+}
+
+#[test]
+fn menu_text() {
+  check("menu_text", &render(State::Menu, &world()));
+}
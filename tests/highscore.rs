@@ -0,0 +1,141 @@
+//! The high score table on disk: missing and damaged files, ranking and a
+//! save/load round trip. Each test works in its own directory under the
+//! system temp dir.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use breakout::highscore::{HighScore, HighScores, MAX_ENTRIES, MAX_NAME_LEN};
+
+/// An empty directory for one test, removed when dropped.
+struct TempDir(PathBuf);
+
+impl TempDir {
+  fn new(name: &str) -> TempDir {
+    let dir = env::temp_dir().join(format!("breakout-highscore-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    TempDir(dir)
+  }
+
+  fn path(&self, file: &str) -> PathBuf {
+    self.0.join(file)
+  }
+}
+
+impl Drop for TempDir {
+  fn drop(&mut self) {
+    let _ = fs::remove_dir_all(&self.0);
+  }
+}
+
+fn entry(name: &str, score: u32) -> HighScore {
+  HighScore { name: name.to_string(), score, level: 1, seed: None, date: String::from("2019-10-01") }
+}
+
+/// Names and scores in table order.
+fn ranking(table: &HighScores) -> Vec<(&str, u32)> {
+  table.entries().iter().map(|e| (e.name.as_str(), e.score)).collect()
+}
+
+#[test]
+fn missing_file_gives_an_empty_table_that_can_be_saved() {
+  let dir = TempDir::new("missing");
+  let path = dir.path("nested/highscores.txt");
+  let mut table = HighScores::load(Some(path.clone()));
+  assert!(table.entries().is_empty());
+  assert!(table.qualifies(1));
+  assert!(!table.qualifies(0), "a run that scored nothing never makes the table");
+
+  table.insert(entry("ann", 10));
+  table.save().unwrap();
+  assert_eq!(ranking(&HighScores::load(Some(path))), vec![("ann", 10)]);
+}
+
+#[test]
+fn malformed_lines_are_skipped() {
+  let dir = TempDir::new("malformed");
+  let path = dir.path("highscores.txt");
+  fs::create_dir_all(&dir.0).unwrap();
+  fs::write(&path, "# breakout high scores v1\n\
+    300\t2\t42\t2019-10-01\tann\n\
+    lots\t2\t42\t2019-10-01\tbob\n\
+    200\t1\tnope\t2019-10-01\tcat\n\
+    100\t1\n\
+    \n\
+    400\t3\t-\t2019-10-02\tdan\n").unwrap();
+  assert_eq!(ranking(&HighScores::load(Some(path))), vec![("dan", 400), ("ann", 300)]);
+}
+
+#[test]
+fn garbage_file_gives_an_empty_table() {
+  let dir = TempDir::new("garbage");
+  fs::create_dir_all(&dir.0).unwrap();
+  let text = dir.path("text.txt");
+  fs::write(&text, "this is not a high score table\n\t\t\t\n").unwrap();
+  assert!(HighScores::load(Some(text)).entries().is_empty());
+  // 不是 UTF-8 的文件读不出来，同样当成空表
+  let binary = dir.path("binary.txt");
+  fs::write(&binary, [0xff, 0xfe, 0x00, 0x80, b'\n']).unwrap();
+  assert!(HighScores::load(Some(binary)).entries().is_empty());
+}
+
+#[test]
+fn low_score_does_not_make_a_full_table() {
+  let mut table = HighScores::load(None);
+  for score in 1..=MAX_ENTRIES as u32 {
+    assert!(table.insert(entry("ann", score * 100)).is_some());
+  }
+  assert!(!table.qualifies(50));
+  assert!(!table.qualifies(100), "tying the lowest score is not enough");
+  assert_eq!(table.insert(entry("bob", 50)), None);
+  assert_eq!(table.entries().len(), MAX_ENTRIES);
+
+  assert!(table.qualifies(150));
+  assert_eq!(table.insert(entry("cat", 150)), Some(MAX_ENTRIES - 1));
+  assert_eq!(table.entries().len(), MAX_ENTRIES);
+  assert_eq!(table.entries().last().map(|e| e.score), Some(150), "the lowest score drops off");
+}
+
+#[test]
+fn equal_scores_keep_the_earlier_entry_first() {
+  let mut table = HighScores::load(None);
+  assert_eq!(table.insert(entry("ann", 500)), Some(0));
+  assert_eq!(table.insert(entry("bob", 500)), Some(1));
+  assert_eq!(table.insert(entry("cat", 600)), Some(0));
+  assert_eq!(ranking(&table), vec![("cat", 600), ("ann", 500), ("bob", 500)]);
+
+  // 读回来时同分的顺序也不变
+  let dir = TempDir::new("equal");
+  let path = dir.path("highscores.txt");
+  fs::create_dir_all(&dir.0).unwrap();
+  fs::write(&path, "500\t1\t-\t2019-10-01\tann\n500\t1\t-\t2019-10-01\tbob\n600\t1\t-\t2019-10-01\tcat\n").unwrap();
+  assert_eq!(ranking(&HighScores::load(Some(path))), vec![("cat", 600), ("ann", 500), ("bob", 500)]);
+}
+
+#[test]
+fn saved_table_loads_back_identically() {
+  let dir = TempDir::new("round-trip");
+  let path = dir.path("highscores.txt");
+  let mut table = HighScores::load(Some(path.clone()));
+  table.insert(HighScore { name: String::from("ann"), score: 1200, level: 3, seed: Some(42), date: String::from("2019-10-01") });
+  table.insert(HighScore { name: String::from("bob smith"), score: 800, level: 2, seed: None, date: String::from("2019-12-31") });
+  table.insert(HighScore { name: String::from("chloé"), score: 800, level: 1, seed: Some(18_446_744_073_709_551_615), date: String::from("2020-02-29") });
+  table.save().unwrap();
+
+  let loaded = HighScores::load(Some(path.clone()));
+  assert_eq!(loaded.entries().len(), table.entries().len());
+  for (a, b) in loaded.entries().iter().zip(table.entries()) {
+    assert_eq!((&a.name, a.score, a.level, a.seed, &a.date), (&b.name, b.score, b.level, b.seed, &b.date));
+  }
+  assert!(fs::read_to_string(&path).unwrap().starts_with("# breakout high scores v1\n"));
+}
+
+#[test]
+fn long_names_are_cut_when_loading() {
+  let dir = TempDir::new("long-name");
+  let path = dir.path("highscores.txt");
+  fs::create_dir_all(&dir.0).unwrap();
+  fs::write(&path, "100\t1\t-\t2019-10-01\tabcdefghijklmnop\n").unwrap();
+  let table = HighScores::load(Some(path));
+  assert_eq!(table.entries()[0].name.chars().count(), MAX_NAME_LEN);
+}
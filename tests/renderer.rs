@@ -0,0 +1,73 @@
+//! Draws frames through `RecordingRenderer` and checks the calls made, in order.
+
+mod common;
+
+use cgmath::{vec2, vec3};
+use breakout::{WIDTH, HEIGHT};
+use breakout::highscore::HighScores;
+use breakout::renderer::{DrawCall, RecordingRenderer, Renderer};
+use breakout::screen::Screen;
+use breakout::state::State;
+use breakout::text;
+use breakout::world::World;
+use common::world;
+
+fn record(state: State, world: &World) -> Vec<DrawCall> {
+  let high_scores = HighScores::load(None);
+  let mut renderer = RecordingRenderer::default();
+  renderer.clear(vec3(0.2, 0.3, 0.3));
+  Screen { state, world, high_scores: &high_scores, name_entry: None }.draw(&mut renderer, 1.0);
+  renderer.present();
+  renderer.calls
+}
+
+fn texture(call: &DrawCall) -> &str {
+  match call {
+    DrawCall::Sprite { texture, .. } => texture,
+    call => panic!("expected a sprite, got {:?}", call),
+  }
+}
+
+#[test]
+fn world_is_drawn_back_to_front() {
+  let mut world = world();
+  world.bricks[0].is_destroyed = true;
+  let calls = record(State::Playing, &world);
+
+  assert_eq!(calls[0], DrawCall::Clear(vec3(0.2, 0.3, 0.3)));
+  assert_eq!(calls[1], DrawCall::Sprite {
+    texture: String::from("res/bg.jpg"),
+    position: vec2(0.0, 0.0),
+    size: vec2(WIDTH as f32, HEIGHT as f32),
+    rotate: 0.0,
+    color: vec3(1.0, 1.0, 1.0),
+  });
+
+  // 砖块按顺序画，已打碎的跳过
+  let mut objects = world.bricks.iter().filter(|brick| !brick.is_destroyed).map(|brick| &brick.game_object).collect::<Vec<_>>();
+  objects.push(&world.player.game_object);
+  objects.extend(world.balls.iter().map(|ball| &ball.game_object));
+  for (call, game_object) in calls[2..].iter().zip(&objects) {
+    assert_eq!(*call, DrawCall::Sprite {
+      texture: String::from(game_object.texture),
+      position: game_object.position,
+      size: game_object.size,
+      rotate: 0.0,
+      color: game_object.color,
+    });
+  }
+
+  // 最后是 HUD 文字
+  let hud = &calls[2 + objects.len()..calls.len() - 1];
+  assert!(!hud.is_empty());
+  assert!(hud.iter().all(|call| texture(call) == text::TEXTURE));
+  assert_eq!(calls.last(), Some(&DrawCall::Present));
+}
+
+#[test]
+fn menu_draws_no_world() {
+  let world = world();
+  let calls = record(State::Menu, &world);
+  assert_eq!(texture(&calls[1]), "res/bg.jpg");
+  assert!(calls[2..calls.len() - 1].iter().all(|call| texture(call) == text::TEXTURE));
+}
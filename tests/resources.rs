@@ -0,0 +1,63 @@
+//! Checks that textures are loaded once and shared, however often they are drawn.
+
+mod common;
+
+use std::rc::Rc;
+use std::cell::Cell;
+use cgmath::vec3;
+use breakout::{WIDTH, HEIGHT};
+use breakout::game_object;
+use breakout::highscore::HighScores;
+use breakout::renderer::Renderer;
+use breakout::resources::ResourceManager;
+use breakout::screen::Screen;
+use breakout::software_renderer::SoftwareRenderer;
+use breakout::state::State;
+use common::world;
+
+#[test]
+fn frames_load_each_texture_once() {
+  let mut world = world();
+  let high_scores = HighScores::load(None);
+  let mut renderer = SoftwareRenderer::new(WIDTH, HEIGHT);
+  let setup_loads = renderer.textures().total_loads();
+  for _ in 0..3 {
+    renderer.clear(vec3(0.2, 0.3, 0.3));
+    Screen { state: State::Playing, world: &world, high_scores: &high_scores, name_entry: None }.draw(&mut renderer, 1.0);
+    renderer.present();
+    world.reset();
+  }
+  // 图集里的纹理不单独加载，其余的各加载一次
+  for &src in game_object::TEXTURES.iter() {
+    let expected = if renderer.atlas().region(src).is_some() { 0 } else { 1 };
+    assert_eq!(renderer.textures().load_count(src), expected, "{}", src);
+  }
+  assert_eq!(renderer.textures().total_loads(), setup_loads);
+}
+
+#[test]
+fn handles_are_shared_until_released() {
+  let loads = Rc::new(Cell::new(0));
+  let counter = Rc::clone(&loads);
+  let mut textures = ResourceManager::new(move |key: &str| {
+    counter.set(counter.get() + 1);
+    key.len()
+  });
+
+  let a = textures.get("res/block.png");
+  let b = textures.get("res/block.png");
+  assert!(Rc::ptr_eq(&a, &b));
+  textures.get("res/paddle.png");
+  assert_eq!(loads.get(), 2);
+  assert_eq!(textures.load_count("res/block.png"), 1);
+
+  // paddle.png 只剩管理器自己持有，会被释放；block.png 还有人在用
+  assert_eq!(textures.release_unused(), 1);
+  drop((a, b));
+  assert_eq!(textures.release_unused(), 1);
+  assert!(textures.is_empty());
+
+  textures.get("res/paddle.png");
+  assert_eq!(textures.load_count("res/paddle.png"), 2);
+  assert_eq!(textures.total_loads(), 3);
+}
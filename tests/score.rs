@@ -0,0 +1,65 @@
+//! Scoring as reported by `World::score_events`.
+
+mod common;
+
+use cgmath::vec2;
+use breakout::score::{self, ScoreEvent};
+use breakout::world::Input;
+use common::{world, DT};
+
+#[test]
+fn world_reports_destroyed_bricks() {
+  let mut world = world();
+  // 最下面一排中可以打碎的砖块，球从正下方打上去
+  let bottom = world.bricks.last().unwrap().game_object.position.y;
+  let (kind, position, size) = world.bricks.iter()
+    .find(|brick| !brick.is_solid && brick.game_object.position.y == bottom)
+    .map(|brick| (brick.kind, brick.game_object.position, brick.game_object.size))
+    .unwrap();
+  let ball = &mut world.balls[0];
+  ball.is_stuck = false;
+  ball.game_object.position = vec2(position.x + size.x / 2.0 - ball.radius, position.y + size.y + 1.0);
+  ball.velocity = vec2(0.0, -500.0);
+
+  world.step(DT, Input::default());
+  assert_eq!(world.score_events, vec![ScoreEvent::BrickDestroyed { kind, points: score::brick_points(kind), multiplier: 1 }]);
+  assert_eq!(world.scoring.score(), score::brick_points(kind));
+  assert!(world.balls[0].velocity.y > 0.0);
+
+  world.step(DT, Input::default());
+  assert!(world.score_events.is_empty(), "events are only kept for one step");
+}
+
+#[test]
+fn world_resets_the_combo_when_the_ball_lands_on_the_paddle() {
+  let mut world = world();
+  world.scoring.brick_destroyed(2);
+  world.scoring.brick_destroyed(2);
+  assert_eq!(world.scoring.multiplier(), 3);
+
+  let (position, size) = (world.player.game_object.position, world.player.game_object.size);
+  let ball = &mut world.balls[0];
+  ball.is_stuck = false;
+  ball.game_object.position = vec2(position.x + size.x / 2.0 - ball.radius, position.y - 2.0 * ball.radius - 1.0);
+  ball.velocity = vec2(0.0, 500.0);
+
+  world.step(DT, Input::default());
+  assert_eq!(world.scoring.multiplier(), 1);
+  assert!(world.balls[0].velocity.y < 0.0);
+}
+
+#[test]
+fn world_reports_the_fast_clear_bonus() {
+  let mut world = world();
+  for brick in world.bricks.iter_mut().filter(|brick| !brick.is_solid) {
+    brick.is_destroyed = true;
+  }
+  world.step(DT, Input::default());
+  match world.score_events[..] {
+    [ScoreEvent::LevelBonus { points, .. }] => {
+      assert!(points > 0);
+      assert_eq!(world.level_bonus, points);
+    },
+    ref events => panic!("expected just the level bonus, got {:?}", events),
+  }
+}
@@ -0,0 +1,76 @@
+//! Drives `State` through a whole run the way the frontends do, checking the
+//! screens it passes through and what each transition does to the world.
+
+mod common;
+
+use cgmath::vec2;
+use breakout::HEIGHT;
+use breakout::state::{State, Event, Transition};
+use breakout::world::{Input, World};
+use common::{world_with_lives, DT};
+
+fn destroy_breakable_bricks(world: &mut World) {
+  for brick in world.bricks.iter_mut().filter(|brick| !brick.is_solid) {
+    brick.is_destroyed = true;
+  }
+}
+
+fn drop_balls(world: &mut World) {
+  for ball in world.balls.iter_mut() {
+    ball.is_stuck = false;
+    ball.game_object.position = vec2(100.0, HEIGHT as f32 + 1.0);
+    ball.velocity = vec2(0.0, 500.0);
+  }
+}
+
+#[test]
+fn menu_to_playing_to_paused_to_level_won_to_game_over() {
+  let mut world = world_with_lives(1);
+  let mut state = State::Menu;
+
+  let started = state.handle(Event::Confirm, &mut world).unwrap();
+  assert_eq!(started, Transition { from: State::Menu, to: State::Playing });
+  assert!(started.started_level());
+  assert_eq!(world.campaign.current(), 0);
+
+  let paused = state.handle(Event::Pause, &mut world).unwrap();
+  assert_eq!(state, State::Paused);
+  assert!(!paused.started_level());
+  let ball = world.balls[0].game_object.position;
+  assert_eq!(state.update(&mut world, DT, Input { launch: true, ..Input::default() }), None);
+  assert_eq!(world.balls[0].game_object.position, ball, "paused game moved");
+  let resumed = state.handle(Event::Pause, &mut world).unwrap();
+  assert_eq!(state, State::Playing);
+  assert!(!resumed.started_level(), "resuming must not reset the level");
+
+  destroy_breakable_bricks(&mut world);
+  let won = state.update(&mut world, DT, Input::default()).unwrap();
+  assert_eq!(won, Transition { from: State::Playing, to: State::LevelWon });
+  assert!(!won.ended_run());
+
+  let next_level = state.handle(Event::Confirm, &mut world).unwrap();
+  assert!(next_level.started_level());
+  assert_eq!(world.campaign.current(), 1);
+  assert!(world.bricks.iter().any(|brick| !brick.is_solid && !brick.is_destroyed));
+
+  drop_balls(&mut world);
+  let lost = state.update(&mut world, DT, Input::default()).unwrap();
+  assert_eq!(lost, Transition { from: State::Playing, to: State::GameOver });
+  assert!(lost.ended_run());
+  assert_eq!(world.lives.remaining(), 0);
+
+  // 重试当前关卡，生命值恢复
+  let retry = state.handle(Event::Confirm, &mut world).unwrap();
+  assert!(retry.started_level());
+  assert_eq!(world.campaign.current(), 1);
+  assert_eq!(world.lives.remaining(), 1);
+}
+
+#[test]
+fn handle_ignores_meaningless_events() {
+  let mut world = world_with_lives(1);
+  let mut state = State::Menu;
+  assert_eq!(state.handle(Event::Pause, &mut world), None);
+  assert_eq!(state.handle(Event::LevelCleared, &mut world), None);
+  assert_eq!(state, State::Menu);
+}
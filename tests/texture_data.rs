@@ -0,0 +1,161 @@
+//! Decodes the small images in `tests/fixtures/` and checks the pixels handed to GL.
+
+use std::path::PathBuf;
+use breakout::assets::{self, Assets};
+use breakout::texture_data::{TextureData, TextureError};
+
+fn fixtures() -> PathBuf {
+  PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures")
+}
+
+fn load(name: &str) -> Result<TextureData, TextureError> {
+  TextureData::load(fixtures().join(name).to_str().unwrap())
+}
+
+fn fixture(name: &str) -> TextureData {
+  load(name).unwrap_or_else(|e| panic!("{}", e))
+}
+
+#[test]
+fn grayscale_png_expands_to_rgb() {
+  let texture = fixture("gray.png");
+  assert_eq!((texture.width, texture.height, texture.has_alpha), (3, 2, false));
+  assert_eq!(texture.data, vec![0, 0, 0, 128, 128, 128, 255, 255, 255, 64, 64, 64, 32, 32, 32, 16, 16, 16]);
+}
+
+#[test]
+fn grayscale_alpha_png_keeps_alpha() {
+  let texture = fixture("gray_alpha.png");
+  assert!(texture.has_alpha);
+  assert_eq!(texture.data, vec![200, 200, 200, 255, 100, 100, 100, 0]);
+}
+
+#[test]
+fn palette_png_without_transparency_is_rgb() {
+  let texture = fixture("palette.png");
+  assert!(!texture.has_alpha);
+  assert_eq!(texture.data, vec![255, 0, 0, 0, 255, 0, 0, 0, 255]);
+}
+
+#[test]
+fn palette_png_with_transparency_is_rgba() {
+  let texture = fixture("palette_alpha.png");
+  assert!(texture.has_alpha);
+  assert_eq!(texture.data, vec![255, 0, 0, 255, 0, 255, 0, 64]);
+}
+
+// 3 像素宽的 RGB 每行 9 字节，数据里不能有对齐用的填充
+#[test]
+fn odd_width_rgb_rows_are_tightly_packed() {
+  let texture = fixture("rgb_odd.png");
+  assert_eq!((texture.width, texture.height, texture.has_alpha), (3, 3, false));
+  assert_eq!(texture.data.len(), 3 * 3 * 3);
+  assert_eq!(&texture.data[9..18], &[10, 20, 30, 40, 50, 60, 70, 80, 90]);
+}
+
+#[test]
+fn rgba_bmp_keeps_alpha() {
+  let texture = fixture("rgba.bmp");
+  assert!(texture.has_alpha);
+  assert_eq!(texture.data, vec![255, 0, 0, 255, 0, 255, 0, 128, 0, 0, 255, 0, 255, 255, 255, 255]);
+}
+
+#[test]
+fn rgba_tga_keeps_alpha() {
+  let texture = fixture("rgba.tga");
+  assert!(texture.has_alpha);
+  assert_eq!(texture.data, vec![255, 0, 0, 255, 0, 255, 0, 128, 0, 0, 255, 0, 255, 255, 255, 255]);
+}
+
+#[test]
+fn jpeg_is_rgb() {
+  let texture = TextureData::from_bytes(assets::embedded("res/bg.jpg").unwrap(), "res/bg.jpg").unwrap();
+  assert!(!texture.has_alpha);
+  assert_eq!(texture.data.len(), (texture.width * texture.height * 3) as usize);
+}
+
+#[test]
+fn premultiply_scales_color_by_alpha() {
+  let mut texture = fixture("rgba.bmp");
+  texture.premultiply();
+  assert!(texture.premultiplied);
+  assert_eq!(texture.data, vec![255, 0, 0, 255, 0, 128, 0, 128, 0, 0, 0, 0, 255, 255, 255, 255]);
+  // 再调用一次不会重复相乘
+  texture.premultiply();
+  assert_eq!(&texture.data[4..8], &[0, 128, 0, 128]);
+}
+
+#[test]
+fn bad_files_are_errors() {
+  match load("not_an_image.png") {
+    Err(TextureError::UnknownFormat(_)) => {},
+    Err(e) => panic!("unexpected error: {}", e),
+    Ok(_) => panic!("decoded garbage"),
+  }
+  match load("truncated.png") {
+    Err(TextureError::Decode(..)) => {},
+    Err(e) => panic!("unexpected error: {}", e),
+    Ok(_) => panic!("decoded a truncated file"),
+  }
+  match load("missing.png") {
+    Err(TextureError::Io(..)) => {},
+    Err(e) => panic!("unexpected error: {}", e),
+    Ok(_) => panic!("decoded a missing file"),
+  }
+}
+
+#[test]
+fn errors_name_the_file() {
+  let e = TextureData::from_bytes(b"not an image", "res/ball.png").err().unwrap();
+  assert_eq!(e.to_string(), "res/ball.png: not an image format we can read");
+  // TGA 没有文件签名，靠扩展名认出来，所以同样的内容是解码失败
+  match TextureData::from_bytes(b"not an image", "res/ball.TGA") {
+    Err(TextureError::Decode(name, _)) => assert_eq!(name, "res/ball.TGA"),
+    Err(e) => panic!("unexpected error: {}", e),
+    Ok(_) => panic!("decoded garbage"),
+  }
+}
+
+#[test]
+fn missing_and_corrupt_assets_are_errors() {
+  let assets = Assets::new(Some(fixtures())).unwrap();
+  match assets.texture("res/nothing.png") {
+    Err(TextureError::Io(key, _)) => assert_eq!(key, "res/nothing.png"),
+    Err(e) => panic!("unexpected error: {}", e),
+    Ok(_) => panic!("loaded an asset that doesn't exist"),
+  }
+  match assets.texture("truncated.png") {
+    Err(TextureError::Decode(key, _)) => assert_eq!(key, "truncated.png"),
+    Err(e) => panic!("unexpected error: {}", e),
+    Ok(_) => panic!("decoded a truncated file"),
+  }
+}
+
+fn assert_checkerboard(texture: &TextureData) {
+  assert_eq!((texture.width, texture.height, texture.has_alpha), (64, 64, false));
+  assert_eq!(texture.data.len(), 64 * 64 * 3);
+  let pixel = |x: usize, y: usize| &texture.data[(y * 64 + x) * 3..][..3];
+  // 8×8 的格子，左上角是品红
+  let (magenta, black) = (&[255, 0, 255][..], &[0, 0, 0][..]);
+  assert_eq!(pixel(0, 0), magenta);
+  assert_eq!(pixel(7, 7), magenta);
+  assert_eq!(pixel(8, 0), black);
+  assert_eq!(pixel(0, 8), black);
+  assert_eq!(pixel(8, 8), magenta);
+  assert_eq!(pixel(63, 63), magenta);
+  assert_eq!(pixel(55, 63), black);
+}
+
+#[test]
+fn missing_texture_is_a_checkerboard() {
+  assert_checkerboard(&TextureData::missing());
+}
+
+#[test]
+fn unloadable_assets_are_drawn_as_the_checkerboard() {
+  let assets = Assets::new(Some(fixtures())).unwrap();
+  assert_checkerboard(&assets.texture_or_missing("res/nothing.png"));
+  assert_checkerboard(&assets.texture_or_missing("not_an_image.png"));
+  // 能加载的贴图不受影响
+  assert_eq!(assets.texture_or_missing("gray.png").width, 3);
+}
@@ -0,0 +1,45 @@
+//! Parses texture manifests and checks the options they give each asset.
+
+use std::fs;
+use breakout::assets::Assets;
+use breakout::texture_options::{self, Filter, ManifestError, TextureOptions, Wrap};
+
+#[test]
+fn manifest_sets_options_per_asset() {
+  let manifest = texture_options::parse_manifest("textures.txt", "
+    # 像素风的砖块
+    res/block.png  filter=nearest wrap=clamp   # 行尾注释
+    res/bg.jpg     mipmaps=on anisotropy=8 min_filter=nearest srgb=on premultiplied=off
+  ").unwrap();
+  assert_eq!(manifest.len(), 2);
+  assert_eq!(manifest["res/block.png"], TextureOptions::new().filter(Filter::Nearest).wrap(Wrap::ClampToEdge));
+  assert_eq!(
+    manifest["res/bg.jpg"],
+    TextureOptions::new().mipmaps(true).anisotropy(8.0).min_filter(Filter::Nearest).srgb(true)
+  );
+}
+
+#[test]
+fn bad_options_name_the_line() {
+  for token in &["filter=cubic", "wrap", "anisotropy=0.5", "mipmaps=maybe", "colour=red"] {
+    let text = format!("res/face.png\nres/block.png {}\n", token);
+    match texture_options::parse_manifest("textures.txt", &text) {
+      Err(ManifestError::InvalidOption { line, token: t, .. }) => assert_eq!((line, &t[..]), (2, *token)),
+      Err(e) => panic!("unexpected error: {}", e),
+      Ok(_) => panic!("accepted `{}`", token),
+    }
+  }
+}
+
+#[test]
+fn override_directory_replaces_the_manifest() {
+  assert_eq!(Assets::default().texture_options("res/block.png"), TextureOptions::new());
+
+  let dir = std::env::temp_dir().join(format!("breakout-manifest-{}", std::process::id()));
+  fs::create_dir_all(&dir).unwrap();
+  fs::write(dir.join("textures.txt"), "res/block.png filter=nearest\n").unwrap();
+  let assets = Assets::new(Some(dir.clone())).unwrap();
+  fs::remove_dir_all(&dir).unwrap();
+  assert_eq!(assets.texture_options("res/block.png"), TextureOptions::new().filter(Filter::Nearest));
+  assert_eq!(assets.texture_options("res/paddle.png"), TextureOptions::new());
+}
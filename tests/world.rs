@@ -0,0 +1,148 @@
+//! Steps a `World` headless, the way the game loop does, and checks what the
+//! player would see happen.
+
+mod common;
+
+use cgmath::vec2;
+use breakout::{WIDTH, HEIGHT};
+use breakout::game_object::PowerUp;
+use breakout::power_up::PowerUpKind;
+use breakout::state::Event;
+use breakout::world::{Input, World};
+use common::{world, DT};
+
+fn remaining_bricks(world: &World) -> usize {
+  world.bricks.iter().filter(|brick| !brick.is_solid && !brick.is_destroyed).count()
+}
+
+/// Sends the only ball off the bottom of the screen.
+fn drop_ball(world: &mut World) {
+  let ball = &mut world.balls[0];
+  ball.is_stuck = false;
+  ball.game_object.position = vec2(100.0, HEIGHT as f32 + 1.0);
+  ball.velocity = vec2(0.0, 500.0);
+}
+
+#[test]
+fn stuck_ball_follows_the_paddle_until_launched() {
+  let mut world = world();
+  for _ in 0..10 {
+    world.step(DT, Input { left: true, ..Input::default() });
+  }
+  let (ball, player_go) = (&world.balls[0], &world.player.game_object);
+  assert!(ball.is_stuck);
+  assert_eq!(ball.game_object.position.x + ball.radius, player_go.position.x + player_go.size.x / 2.0);
+  assert_eq!(ball.game_object.position.y + 2.0 * ball.radius, player_go.position.y);
+
+  let before = ball.game_object.position;
+  world.step(DT, Input { launch: true, ..Input::default() });
+  let ball = &world.balls[0];
+  assert!(!ball.is_stuck);
+  assert!(ball.velocity.y < 0.0);
+  assert!(ball.game_object.position.y < before.y);
+}
+
+#[test]
+fn launched_ball_breaks_bricks() {
+  let mut world = world();
+  let bricks = remaining_bricks(&world);
+  world.step(DT, Input { launch: true, ..Input::default() });
+  for _ in 0..120 {
+    if world.scoring.score() > 0 {
+      break;
+    }
+    assert_eq!(world.step(DT, Input::default()), None);
+  }
+  assert!(world.scoring.score() > 0, "no brick hit within two seconds");
+  assert!(remaining_bricks(&world) < bricks);
+  assert!(world.balls[0].velocity.y > 0.0, "ball should come back down after the hit");
+}
+
+#[test]
+fn losing_a_ball_costs_a_life_and_sticks_a_new_one_to_the_paddle() {
+  let mut world = world();
+  let bricks = remaining_bricks(&world);
+  drop_ball(&mut world);
+  assert_eq!(world.step(DT, Input::default()), None);
+  assert_eq!(world.lives.remaining(), 2);
+  assert_eq!(world.balls.len(), 1);
+  assert!(world.balls[0].is_stuck);
+  assert_eq!(remaining_bricks(&world), bricks, "bricks are kept after losing a ball");
+}
+
+#[test]
+fn losing_one_of_several_balls_is_free() {
+  let mut world = world();
+  world.power_ups.push(PowerUp::new(PowerUpKind::MultiBall, world.player.game_object.position));
+  world.step(DT, Input::default());
+  assert_eq!(world.balls.len(), 3);
+  drop_ball(&mut world);
+  world.step(DT, Input::default());
+  assert_eq!(world.balls.len(), 2);
+  assert_eq!(world.lives.remaining(), 3);
+}
+
+#[test]
+fn clearing_the_last_brick_advances_the_campaign() {
+  let mut world = world();
+  for brick in world.bricks.iter_mut().filter(|brick| !brick.is_solid) {
+    brick.is_destroyed = true;
+  }
+  assert_eq!(world.step(DT, Input::default()), Some(Event::LevelCleared));
+  assert_eq!(world.campaign.current(), 1);
+
+  world.reset();
+  assert!(remaining_bricks(&world) > 0);
+  for brick in world.bricks.iter_mut().filter(|brick| !brick.is_solid) {
+    brick.is_destroyed = true;
+  }
+  assert_eq!(world.step(DT, Input::default()), Some(Event::CampaignCleared));
+}
+
+#[test]
+fn losing_the_last_life_ends_the_run() {
+  let mut world = world();
+  for lives in (1..3).rev() {
+    drop_ball(&mut world);
+    assert_eq!(world.step(DT, Input::default()), None);
+    assert_eq!(world.lives.remaining(), lives);
+  }
+  drop_ball(&mut world);
+  assert_eq!(world.step(DT, Input::default()), Some(Event::OutOfLives));
+  assert_eq!(world.lives.remaining(), 0);
+
+  world.new_run();
+  world.reset();
+  assert_eq!(world.lives.remaining(), 3);
+  assert_eq!(world.scoring.score(), 0);
+}
+
+#[test]
+fn losing_a_ball_with_launch_held_loses_one_life() {
+  let mut world = world();
+  let launch = Input { launch: true, ..Input::default() };
+  drop_ball(&mut world);
+  for _ in 0..10 {
+    assert_eq!(world.step(DT, launch), None);
+  }
+  assert_eq!(world.lives.remaining(), 2);
+  assert_eq!(world.balls.len(), 1);
+  let (ball, player_go) = (&world.balls[0], &world.player.game_object);
+  assert!(ball.game_object.position.y < player_go.position.y, "ball should be flying up, not inside the paddle");
+  assert!(ball.velocity.y < 0.0);
+}
+
+#[test]
+fn growing_paddle_stays_on_screen() {
+  let mut world = world();
+  let player_go = &mut world.player.game_object;
+  player_go.position.x = WIDTH as f32 - player_go.size.x;
+  let position = player_go.position;
+  world.power_ups.push(PowerUp::new(PowerUpKind::PadSizeIncrease, position));
+
+  world.step(DT, Input::default());
+  assert!(world.active_power_ups.is_active(PowerUpKind::PadSizeIncrease));
+  let player_go = &world.player.game_object;
+  assert_eq!(player_go.size.x, 150.0);
+  assert_eq!(player_go.position.x + player_go.size.x, WIDTH as f32);
+}